@@ -13,6 +13,9 @@ pub enum Icon {
     ChevronLeft,
     At,
     NewSection,
+    List,
+    ListCheck,
+    User,
 }
 impl Icon {
     pub fn as_char(&self) -> char {
@@ -30,6 +33,9 @@ impl Icon {
             Icon::ChevronLeft => '\u{E00B}',
             Icon::At => '\u{E00C}',
             Icon::NewSection => '\u{E00D}',
+            Icon::List => '\u{E00E}',
+            Icon::ListCheck => '\u{E00F}',
+            Icon::User => '\u{E010}',
         }
     }
 }
@@ -1,6 +1,6 @@
-use iced::{Color, Element, Subscription, Task, application, theme, window};
+use iced::{Color, Element, Subscription, Task, application, exit, theme, window};
 
-use crate::{Config, pages::*};
+use crate::{Config, pages::*, spaced::Spaced};
 
 #[derive(Debug)]
 pub enum State {
@@ -12,6 +12,7 @@ pub enum State {
 enum Message {
     Setup(setup::Message),
     Main(main::Message),
+    CloseRequested,
 }
 
 impl State {
@@ -22,6 +23,7 @@ impl State {
         application("Akron", Self::update, Self::view)
             .font(include_bytes!("../assets/icons.ttf").as_slice())
             .subscription(Self::subscription)
+            .exit_on_close_request(false)
             .window(window::Settings {
                 min_size: Some((1300.0, 500.0).into()),
                 icon: Some(
@@ -54,11 +56,24 @@ impl State {
             .run_with(move || (state, task))
     }
 
+    fn node(&self) -> Option<&Spaced> {
+        match self {
+            Self::Setup(state) => state.node(),
+            Self::Main(state) => state.node(),
+        }
+    }
+
     fn update(&mut self, message: Message) -> Task<Message> {
+        if let Message::CloseRequested = message {
+            return match self.node() {
+                Some(node) => Task::future(node.shutdown()).then(|_| exit()),
+                None => exit(),
+            };
+        }
         match (&mut *self, message) {
             (Self::Setup(state), Message::Setup(message)) => match state.update(message) {
-                setup::Action::Return(config, client) => {
-                    let (state, task) = main::State::run(config, client);
+                setup::Action::Return(config, client, node) => {
+                    let (state, task) = main::State::run(config, client, node);
                     let task = task.map(Message::Main);
                     *self = Self::Main(state);
                     task
@@ -66,12 +81,16 @@ impl State {
                 setup::Action::Task(task) => task.map(Message::Setup),
             },
             (Self::Main(state), Message::Main(message)) => match state.update(message) {
-                main::Action::Return(mut config) => {
-                    config.reset();
+                main::Action::Return(config) => {
+                    // the next backend may need the ports held by the current node
+                    let shutdown = state.node().map(|node| node.shutdown());
                     let (state, task) = setup::State::run(config);
                     let task = task.map(Message::Setup);
                     *self = Self::Setup(state);
-                    task
+                    match shutdown {
+                        Some(shutdown) => Task::future(shutdown).discard().chain(task),
+                        None => task,
+                    }
                 }
                 main::Action::Task(task) => task.map(Message::Main),
             },
@@ -87,10 +106,11 @@ impl State {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        let close_requests = window::close_requests().map(|_| Message::CloseRequested);
         if let Self::Main(state) = self {
-            state.subscription().map(Message::Main)
+            Subscription::batch([close_requests, state.subscription().map(Message::Main)])
         } else {
-            Subscription::none()
+            close_requests
         }
     }
 }
@@ -0,0 +1,339 @@
+use clap::{Parser, Subcommand};
+use iced::Task;
+use serde::Serialize;
+use std::{path::PathBuf, process::ExitCode};
+
+use akron::{
+    Config, Overrides,
+    client::*,
+    helpers::{format_amount, listing_from_str, slabel_from_str},
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "akron-cli",
+    version,
+    about = "Manage Akron wallets from the command line"
+)]
+struct Cli {
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(flatten)]
+    overrides: Overrides,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List wallets
+    Wallets,
+    /// Show wallet balance
+    Balance,
+    /// List spaces the wallet bids on or owns
+    Spaces,
+    /// Send coins
+    Send {
+        recipient: String,
+        /// Amount in sat
+        amount: u64,
+        /// Fee rate in sat/vB, estimated if omitted
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// Open an auction for a space
+    Open {
+        space: String,
+        /// Amount in sat
+        amount: u64,
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// Bid on a space
+    Bid {
+        space: String,
+        /// Amount in sat
+        amount: u64,
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// Register a won space
+    Register {
+        space: String,
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// Renew an owned space
+    Renew {
+        space: String,
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// Transfer owned spaces to one recipient in a single transaction
+    Transfer {
+        #[arg(required = true)]
+        spaces: Vec<String>,
+        /// Bitcoin address or @space to send to
+        #[arg(long)]
+        to: String,
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// Buy a space from a listing, given as JSON or a path to a JSON file
+    Buy {
+        listing: String,
+        #[arg(long)]
+        fee_rate: Option<u64>,
+    },
+    /// List an owned space for sale
+    Sell {
+        space: String,
+        /// Price in sat
+        price: u64,
+    },
+    /// Sign a Nostr event file with a space
+    SignEvent { space: String, event: PathBuf },
+}
+
+async fn perform<T>(task: Task<T>) -> Result<T, String> {
+    outputs(task)
+        .await
+        .pop()
+        .ok_or_else(|| "Client task produced no result".to_string())
+}
+
+fn parse_space(space: &str) -> Result<SLabel, String> {
+    slabel_from_str(space.trim_start_matches('@')).ok_or_else(|| format!("Invalid space {}", space))
+}
+
+fn parse_fee_rate(fee_rate: Option<u64>) -> Result<Option<FeeRate>, String> {
+    fee_rate
+        .map(|fee_rate| {
+            FeeRate::from_sat_per_vb(fee_rate).ok_or_else(|| "Invalid fee rate".to_string())
+        })
+        .transpose()
+}
+
+fn read_json(value: &str) -> Result<String, String> {
+    if value.trim_start().starts_with('{') {
+        Ok(value.to_string())
+    } else {
+        std::fs::read_to_string(value).map_err(|e| format!("Failed to read {}: {}", value, e))
+    }
+}
+
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn print<T: Serialize>(&self, value: &T, human: impl FnOnce(&T) -> String) {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(value).unwrap());
+        } else {
+            println!("{}", human(value));
+        }
+    }
+
+    fn print_response(&self, response: &WalletResponse) {
+        self.print(response, |response| {
+            response
+                .result
+                .iter()
+                .map(|tx| tx.txid.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
+    fn print_error(&self, err: &str) {
+        if self.json {
+            println!("{}", serde_json::json!({ "error": err }));
+        } else {
+            eprintln!("Error: {}", err);
+        }
+    }
+}
+
+async fn run(cli: Cli, output: &Output) -> Result<(), String> {
+    let mut config = Config::load_with(&cli.overrides);
+    if let Some(err) = config.take_load_error() {
+        eprintln!("Warning: {}", err);
+    }
+    if config.is_locked() {
+        let passphrase = std::env::var("AKRON_PASSPHRASE")
+            .map_err(|_| "Config is locked, set AKRON_PASSPHRASE to unlock it".to_string())?;
+        config.unlock(&passphrase)?;
+    }
+    let client = config
+        .backend
+        .as_ref()
+        .ok_or_else(|| "No backend configured, run Akron first".to_string())?
+        .connect()?;
+    let wallet_name = config.wallet;
+    let wallet = || {
+        wallet_name
+            .clone()
+            .ok_or_else(|| "No wallet selected".to_string())
+    };
+
+    match cli.command {
+        Command::Wallets => {
+            let wallets = perform(client.list_wallets()).await??;
+            output.print(&wallets, |wallets| wallets.join("\n"));
+        }
+        Command::Balance => {
+            let balance = perform(client.get_wallet_balance(wallet()?))
+                .await?
+                .result?;
+            output.print(&balance, |balance| format_amount(balance.balance, false));
+        }
+        Command::Spaces => {
+            let spaces = perform(client.get_wallet_spaces(wallet()?)).await?.result?;
+            output.print(&spaces, |spaces| {
+                let names = |title: &str, spaces: &Vec<FullSpaceOut>| {
+                    spaces.iter().fold(title.to_string(), |lines, out| {
+                        format!("{}\n  {}", lines, out.spaceout.space.as_ref().unwrap().name)
+                    })
+                };
+                [
+                    names("Winning:", &spaces.winning),
+                    names("Outbid:", &spaces.outbid),
+                    names("Owned:", &spaces.owned),
+                ]
+                .join("\n")
+            });
+        }
+        Command::Send {
+            recipient,
+            amount,
+            fee_rate,
+        } => {
+            let response = perform(client.send_coins(
+                wallet()?,
+                vec![(recipient, Amount::from_sat(amount))],
+                parse_fee_rate(fee_rate)?,
+                TxOptions::default(),
+            ))
+            .await?
+            .result?;
+            output.print_response(&response);
+        }
+        Command::Open {
+            space,
+            amount,
+            fee_rate,
+        } => {
+            let response = perform(client.open_space(
+                wallet()?,
+                parse_space(&space)?,
+                Amount::from_sat(amount),
+                parse_fee_rate(fee_rate)?,
+            ))
+            .await?
+            .result?;
+            output.print_response(&response);
+        }
+        Command::Bid {
+            space,
+            amount,
+            fee_rate,
+        } => {
+            let response = perform(client.bid_space(
+                wallet()?,
+                parse_space(&space)?,
+                Amount::from_sat(amount),
+                parse_fee_rate(fee_rate)?,
+            ))
+            .await?
+            .result?;
+            output.print_response(&response);
+        }
+        Command::Register { space, fee_rate } => {
+            let response = perform(client.register_space(
+                wallet()?,
+                parse_space(&space)?,
+                parse_fee_rate(fee_rate)?,
+            ))
+            .await?
+            .result?;
+            output.print_response(&response);
+        }
+        Command::Renew { space, fee_rate } => {
+            let response = perform(client.renew_space(
+                wallet()?,
+                parse_space(&space)?,
+                parse_fee_rate(fee_rate)?,
+            ))
+            .await?
+            .result?;
+            output.print_response(&response);
+        }
+        Command::Transfer {
+            spaces,
+            to,
+            fee_rate,
+        } => {
+            let response = perform(
+                client.send_space(
+                    wallet()?,
+                    to,
+                    spaces
+                        .iter()
+                        .map(|space| parse_space(space))
+                        .collect::<Result<_, _>>()?,
+                    parse_fee_rate(fee_rate)?,
+                    TxOptions::default(),
+                ),
+            )
+            .await?
+            .result?;
+            output.print_response(&response);
+        }
+        Command::Buy { listing, fee_rate } => {
+            let listing = listing_from_str(&read_json(&listing)?)
+                .ok_or_else(|| "Invalid listing".to_string())?;
+            perform(client.buy_space(wallet()?, listing, parse_fee_rate(fee_rate)?))
+                .await?
+                .result?;
+            output.print(&(), |_| "Space bought".to_string());
+        }
+        Command::Sell { space, price } => {
+            let listing = perform(client.sell_space(
+                wallet()?,
+                parse_space(&space)?,
+                Amount::from_sat(price),
+            ))
+            .await?
+            .result?;
+            output.print(&listing, |listing| {
+                serde_json::to_string_pretty(listing).unwrap()
+            });
+        }
+        Command::SignEvent { space, event } => {
+            let event = std::fs::read_to_string(&event)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            let event: NostrEvent =
+                serde_json::from_str(&event).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+            let event = perform(client.sign_event(wallet()?, parse_space(&space)?, event))
+                .await?
+                .result?;
+            output.print(&event, |event| serde_json::to_string_pretty(event).unwrap());
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let output = Output { json: cli.json };
+    match run(cli, &output).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            output.print_error(&err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,9 +1,15 @@
-use iced::Task;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use iced::{Task, futures::StreamExt};
 use jsonrpsee::{
-    core::ClientError,
-    http_client::{HttpClient, HttpClientBuilder},
+    core::{ClientError, client::ClientT},
+    http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder},
+    rpc_params,
 };
 
+mod transport;
+
+use spaces_client::config::ExtendedNetwork;
+
 use spaces_client::rpc::{
     BidParams, OpenParams, RegisterParams, RpcClient, RpcWalletRequest, RpcWalletTxBuilder,
     SendCoinsParams, TransferSpacesParams,
@@ -15,7 +21,7 @@ pub use spaces_client::{
 };
 pub use spaces_protocol::{Covenant, FullSpaceOut, bitcoin::Txid, slabel::SLabel};
 pub use spaces_wallet::{
-    Balance, Listing,
+    Balance, Listing, WalletOutput,
     bitcoin::{Amount, FeeRate, OutPoint},
     export::WalletExport,
     nostr::NostrEvent,
@@ -27,7 +33,137 @@ pub use spaces_wallet::{
 
 #[derive(Debug)]
 pub struct Client {
-    client: HttpClient,
+    client: HttpClient<transport::Backend>,
+}
+
+/// Options passed through to the wallet's transaction builder. Spaced picks the
+/// inputs itself, the builder has no way to pin or exclude outpoints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TxOptions {
+    pub confirmed_only: bool,
+    /// Value of the outputs created for spaces, the wallet default if unset.
+    pub dust: Option<Amount>,
+}
+
+/// Fee rates in sat/vB bitcoind expects to confirm within 2, 6 and 144 blocks. Spaced has
+/// no fee estimation RPC, so they are only known when Akron can reach bitcoind itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimates {
+    pub fast: u64,
+    pub normal: u64,
+    pub slow: u64,
+}
+
+impl FeeEstimates {
+    /// Slow, normal and fast rates, in the order the forms offer them.
+    pub fn rates(self) -> [FeeRate; 3] {
+        [self.slow, self.normal, self.fast].map(FeeRate::from_sat_per_vb_unchecked)
+    }
+
+    /// Rate a transaction goes out with when the fee rate is left empty.
+    pub fn auto(self) -> FeeRate {
+        FeeRate::from_sat_per_vb_unchecked(self.normal)
+    }
+}
+
+/// A single wallet action, several of them can go into one transaction.
+#[derive(Debug, Clone)]
+pub enum TxRequest {
+    SendCoins { to: String, amount: Amount },
+    Open { slabel: SLabel, amount: Amount },
+    Bid { slabel: SLabel, amount: Amount },
+    Register { slabel: SLabel },
+    Renew { slabels: Vec<SLabel> },
+    Transfer { slabels: Vec<SLabel>, to: String },
+}
+
+impl TxRequest {
+    fn into_rpc(self) -> RpcWalletRequest {
+        match self {
+            Self::SendCoins { to, amount } => {
+                RpcWalletRequest::SendCoins(SendCoinsParams { amount, to })
+            }
+            Self::Open { slabel, amount } => RpcWalletRequest::Open(OpenParams {
+                name: slabel.to_string(),
+                amount: amount.to_sat(),
+            }),
+            Self::Bid { slabel, amount } => RpcWalletRequest::Bid(BidParams {
+                name: slabel.to_string(),
+                amount: amount.to_sat(),
+            }),
+            Self::Register { slabel } => RpcWalletRequest::Register(RegisterParams {
+                name: slabel.to_string(),
+                to: None,
+            }),
+            Self::Renew { slabels } => RpcWalletRequest::Transfer(TransferSpacesParams {
+                spaces: slabels.iter().map(SLabel::to_string).collect(),
+                to: None,
+            }),
+            Self::Transfer { slabels, to } => RpcWalletRequest::Transfer(TransferSpacesParams {
+                spaces: slabels.iter().map(SLabel::to_string).collect(),
+                to: Some(to),
+            }),
+        }
+    }
+}
+
+/// A transaction held back until the user confirms it.
+#[derive(Debug, Clone)]
+pub enum PendingTx {
+    Send {
+        requests: Vec<TxRequest>,
+        fee_rate: Option<FeeRate>,
+        tx_options: TxOptions,
+    },
+    Buy {
+        listing: Listing,
+        fee_rate: Option<FeeRate>,
+    },
+}
+
+/// Connection settings for a remote spaced, empty strings mean unset.
+#[derive(Debug, Default)]
+pub struct ClientOptions<'a> {
+    pub user: &'a str,
+    pub password: &'a str,
+    pub token: &'a str,
+    pub ca_cert: &'a str,
+    pub fingerprint: &'a str,
+    pub proxy: &'a str,
+}
+
+fn is_https(url: &str) -> bool {
+    url.get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+}
+
+fn proxy_address(proxy: &str) -> &str {
+    proxy
+        .strip_prefix("socks5h://")
+        .or_else(|| proxy.strip_prefix("socks5://"))
+        .unwrap_or(proxy)
+}
+
+pub fn check_proxy(proxy: &str, rpc_url: &str) -> Task<ClientResult<()>> {
+    let proxy = proxy_address(proxy).to_string();
+    let rpc_url = rpc_url.to_string();
+    Task::future(async move { transport::check_proxy(&proxy, &rpc_url).await })
+}
+
+fn auth_header(value: String) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "authorization",
+        HeaderValue::from_str(&value).map_err(|e| e.to_string())?,
+    );
+    Ok(headers)
+}
+
+fn basic_auth_header(user: &str, password: &str) -> Result<HeaderMap, String> {
+    auth_header(format!(
+        "Basic {}",
+        BASE64.encode(format!("{}:{}", user, password))
+    ))
 }
 
 pub type ClientResult<T> = Result<T, String>;
@@ -39,10 +175,63 @@ fn map_result<T>(result: Result<T, ClientError>) -> ClientResult<T> {
     })
 }
 
+/// Why a data request failed, only transport errors say anything about the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// Spaced could not be reached or did not answer in time.
+    Transport(String),
+    /// Spaced answered and rejected the call.
+    Call(String),
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transport(err) | Self::Call(err) => f.write_str(err),
+        }
+    }
+}
+
+impl From<RequestError> for String {
+    fn from(err: RequestError) -> Self {
+        err.to_string()
+    }
+}
+
+pub type RequestResult<T> = Result<T, RequestError>;
+
+fn map_request_result<T>(result: Result<T, ClientError>) -> RequestResult<T> {
+    result.map_err(|e| match e {
+        ClientError::Transport(_) | ClientError::RequestTimeout => {
+            RequestError::Transport(e.to_string())
+        }
+        ClientError::Call(e) => RequestError::Call(e.message().to_string()),
+        _ => RequestError::Call(e.to_string()),
+    })
+}
+
+/// Runs a task outside of the iced runtime and collects what it produces.
+pub async fn outputs<T>(task: Task<T>) -> Vec<T> {
+    match iced_runtime::task::into_stream(task) {
+        Some(stream) => {
+            stream
+                .filter_map(|action| async move {
+                    match action {
+                        iced_runtime::Action::Output(output) => Some(output),
+                        _ => None,
+                    }
+                })
+                .collect()
+                .await
+        }
+        None => Vec::new(),
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct WalletResult<T> {
+pub struct WalletResult<T, E = String> {
     pub label: String,
-    pub result: Result<T, String>,
+    pub result: Result<T, E>,
 }
 
 fn map_wallet_result<T>((label, result): (String, Result<T, ClientError>)) -> WalletResult<T> {
@@ -52,9 +241,46 @@ fn map_wallet_result<T>((label, result): (String, Result<T, ClientError>)) -> Wa
     }
 }
 
+fn map_wallet_request_result<T>(
+    (label, result): (String, Result<T, ClientError>),
+) -> WalletResult<T, RequestError> {
+    WalletResult {
+        label,
+        result: map_request_result(result),
+    }
+}
+
 impl Client {
-    pub fn new(rpc_url: &str) -> Result<Self, String> {
+    pub fn new(rpc_url: &str, options: ClientOptions) -> Result<Self, String> {
+        let secured = [
+            options.user,
+            options.token,
+            options.ca_cert,
+            options.fingerprint,
+        ]
+        .iter()
+        .any(|option| !option.is_empty());
+        // credentials would go out in cleartext and a pin would never be checked
+        if secured && !is_https(rpc_url) {
+            return Err(
+                "Credentials and certificate pinning need an https:// spaced URL".to_string(),
+            );
+        }
+        let headers = if !options.token.is_empty() {
+            auth_header(format!("Bearer {}", options.token))?
+        } else if !options.user.is_empty() {
+            basic_auth_header(options.user, options.password)?
+        } else {
+            HeaderMap::new()
+        };
+        let layer = transport::BackendLayer::new(
+            options.ca_cert,
+            options.fingerprint,
+            proxy_address(options.proxy),
+        )?;
         let client = HttpClientBuilder::default()
+            .set_headers(headers)
+            .set_http_middleware(tower::ServiceBuilder::new().layer(layer))
             .build(rpc_url)
             .map_err(|e| e.to_string())?;
         Ok(Self { client })
@@ -68,7 +294,7 @@ impl Client {
     pub fn get_space_info(
         &self,
         slabel: SLabel,
-    ) -> Task<ClientResult<(SLabel, Option<FullSpaceOut>)>> {
+    ) -> Task<RequestResult<(SLabel, Option<FullSpaceOut>)>> {
         let client = self.client.clone();
         Task::perform(
             async move {
@@ -78,13 +304,16 @@ impl Client {
                 let result = client.get_space(&hash).await;
                 result.map(|r| (slabel, r))
             },
-            map_result,
+            map_request_result,
         )
     }
 
-    pub fn list_wallets(&self) -> Task<ClientResult<Vec<String>>> {
+    pub fn list_wallets(&self) -> Task<RequestResult<Vec<String>>> {
         let client = self.client.clone();
-        Task::perform(async move { client.list_wallets().await }, map_result)
+        Task::perform(
+            async move { client.list_wallets().await },
+            map_request_result,
+        )
     }
 
     pub fn create_wallet(&self, wallet: String) -> Task<WalletResult<()>> {
@@ -98,14 +327,14 @@ impl Client {
         )
     }
 
-    pub fn load_wallet(&self, wallet: String) -> Task<WalletResult<()>> {
+    pub fn load_wallet(&self, wallet: String) -> Task<WalletResult<(), RequestError>> {
         let client = self.client.clone();
         Task::perform(
             async move {
                 let result = client.wallet_load(&wallet).await;
                 (wallet, result)
             },
-            map_wallet_result,
+            map_wallet_request_result,
         )
     }
 
@@ -138,36 +367,42 @@ impl Client {
         }
     }
 
-    pub fn get_wallet_info(&self, wallet: String) -> Task<WalletResult<WalletInfoWithProgress>> {
+    pub fn get_wallet_info(
+        &self,
+        wallet: String,
+    ) -> Task<WalletResult<WalletInfoWithProgress, RequestError>> {
         let client = self.client.clone();
         Task::perform(
             async move {
                 let result = client.wallet_get_info(&wallet).await;
                 (wallet, result)
             },
-            map_wallet_result,
+            map_wallet_request_result,
         )
     }
 
-    pub fn get_wallet_balance(&self, wallet: String) -> Task<WalletResult<Balance>> {
+    pub fn get_wallet_balance(&self, wallet: String) -> Task<WalletResult<Balance, RequestError>> {
         let client = self.client.clone();
         Task::perform(
             async move {
                 let result = client.wallet_get_balance(&wallet).await;
                 (wallet, result)
             },
-            map_wallet_result,
+            map_wallet_request_result,
         )
     }
 
-    pub fn get_wallet_spaces(&self, wallet: String) -> Task<WalletResult<ListSpacesResponse>> {
+    pub fn get_wallet_spaces(
+        &self,
+        wallet: String,
+    ) -> Task<WalletResult<ListSpacesResponse, RequestError>> {
         let client = self.client.clone();
         Task::perform(
             async move {
                 let result = client.wallet_list_spaces(&wallet).await;
                 (wallet, result)
             },
-            map_wallet_result,
+            map_wallet_request_result,
         )
     }
 
@@ -175,14 +410,14 @@ impl Client {
         &self,
         wallet: String,
         count: usize,
-    ) -> Task<WalletResult<Vec<TxInfo>>> {
+    ) -> Task<WalletResult<Vec<TxInfo>, RequestError>> {
         let client = self.client.clone();
         Task::perform(
             async move {
                 let result = client.wallet_list_transactions(&wallet, count, 0).await;
                 (wallet, result)
             },
-            map_wallet_result,
+            map_wallet_request_result,
         )
     }
 
@@ -190,23 +425,37 @@ impl Client {
         &self,
         wallet: String,
         address_kind: AddressKind,
-    ) -> Task<WalletResult<(AddressKind, String)>> {
+    ) -> Task<WalletResult<(AddressKind, String), RequestError>> {
         let client = self.client.clone();
         Task::perform(
             async move {
                 let result = client.wallet_get_new_address(&wallet, address_kind).await;
                 (wallet, result.map(|r| (address_kind, r)))
             },
-            map_wallet_result,
+            map_wallet_request_result,
+        )
+    }
+
+    pub fn get_wallet_unspent(
+        &self,
+        wallet: String,
+    ) -> Task<WalletResult<Vec<WalletOutput>, RequestError>> {
+        let client = self.client.clone();
+        Task::perform(
+            async move {
+                let result = client.wallet_list_unspent(&wallet).await;
+                (wallet, result)
+            },
+            map_wallet_request_result,
         )
     }
 
     pub fn send_coins(
         &self,
         wallet: String,
-        recipient: String,
-        amount: Amount,
+        payments: Vec<(String, Amount)>,
         fee_rate: Option<FeeRate>,
+        tx_options: TxOptions,
     ) -> Task<WalletResult<WalletResponse>> {
         let client = self.client.clone();
         Task::perform(
@@ -216,14 +465,16 @@ impl Client {
                         &wallet,
                         RpcWalletTxBuilder {
                             bidouts: None,
-                            requests: vec![RpcWalletRequest::SendCoins(SendCoinsParams {
-                                amount,
-                                to: recipient,
-                            })],
+                            requests: payments
+                                .into_iter()
+                                .map(|(to, amount)| {
+                                    RpcWalletRequest::SendCoins(SendCoinsParams { amount, to })
+                                })
+                                .collect(),
                             fee_rate,
-                            dust: None,
+                            dust: tx_options.dust,
                             force: false,
-                            confirmed_only: false,
+                            confirmed_only: tx_options.confirmed_only,
                             skip_tx_check: false,
                         },
                     )
@@ -368,10 +619,11 @@ impl Client {
         &self,
         wallet: String,
         recipient: String,
-        slabel: SLabel,
+        slabels: Vec<SLabel>,
         fee_rate: Option<FeeRate>,
+        tx_options: TxOptions,
     ) -> Task<WalletResult<WalletResponse>> {
-        let name = slabel.to_string();
+        let spaces = slabels.iter().map(SLabel::to_string).collect();
         let client = self.client.clone();
         Task::perform(
             async move {
@@ -381,13 +633,43 @@ impl Client {
                         RpcWalletTxBuilder {
                             bidouts: None,
                             requests: vec![RpcWalletRequest::Transfer(TransferSpacesParams {
-                                spaces: vec![name],
+                                spaces,
                                 to: Some(recipient),
                             })],
                             fee_rate,
-                            dust: None,
+                            dust: tx_options.dust,
                             force: false,
-                            confirmed_only: false,
+                            confirmed_only: tx_options.confirmed_only,
+                            skip_tx_check: false,
+                        },
+                    )
+                    .await;
+                (wallet, result)
+            },
+            map_wallet_result,
+        )
+    }
+
+    pub fn send_requests(
+        &self,
+        wallet: String,
+        requests: Vec<TxRequest>,
+        fee_rate: Option<FeeRate>,
+        tx_options: TxOptions,
+    ) -> Task<WalletResult<WalletResponse>> {
+        let client = self.client.clone();
+        Task::perform(
+            async move {
+                let result = client
+                    .wallet_send_request(
+                        &wallet,
+                        RpcWalletTxBuilder {
+                            bidouts: None,
+                            requests: requests.into_iter().map(TxRequest::into_rpc).collect(),
+                            fee_rate,
+                            dust: tx_options.dust,
+                            force: false,
+                            confirmed_only: tx_options.confirmed_only,
                             skip_tx_check: false,
                         },
                     )
@@ -398,6 +680,38 @@ impl Client {
         )
     }
 
+    /// Broadcasts a transaction the user confirmed, returning the first txid.
+    pub fn broadcast(
+        &self,
+        wallet: String,
+        pending: PendingTx,
+    ) -> Task<WalletResult<Option<Txid>>> {
+        match pending {
+            PendingTx::Send {
+                requests,
+                fee_rate,
+                tx_options,
+            } => self
+                .send_requests(wallet, requests, fee_rate, tx_options)
+                .map(|r| WalletResult {
+                    label: r.label,
+                    result: r
+                        .result
+                        .map(|response| response.result.first().map(|tx| tx.txid)),
+                }),
+            PendingTx::Buy { listing, fee_rate } => {
+                let client = self.client.clone();
+                Task::perform(
+                    async move {
+                        let result = client.wallet_buy(&wallet, listing, fee_rate, false).await;
+                        (wallet, result.map(|tx| Some(tx.txid)))
+                    },
+                    map_wallet_result,
+                )
+            }
+        }
+    }
+
     pub fn bump_fee(
         &self,
         wallet: String,
@@ -465,3 +779,92 @@ impl Client {
         )
     }
 }
+
+#[derive(Debug)]
+pub struct BitcoindClient {
+    client: HttpClient,
+}
+
+fn map_bitcoind_result<T>(result: Result<T, ClientError>) -> ClientResult<T> {
+    result.map_err(|e| match e {
+        ClientError::Transport(e) if e.to_string().contains("401") => {
+            "Bitcoind authentication failed".to_string()
+        }
+        ClientError::Transport(e) => format!("Failed to connect to bitcoind: {}", e),
+        ClientError::Call(e) => e.message().to_string(),
+        _ => e.to_string(),
+    })
+}
+
+pub fn bitcoind_chain(network: ExtendedNetwork) -> &'static str {
+    match network {
+        ExtendedNetwork::Mainnet => "main",
+        ExtendedNetwork::Testnet => "test",
+        ExtendedNetwork::Testnet4 => "testnet4",
+        ExtendedNetwork::Signet => "signet",
+        ExtendedNetwork::Regtest => "regtest",
+    }
+}
+
+pub fn read_bitcoind_cookie(path: &str) -> Result<(String, String), String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read auth cookie: {}", e))?;
+    contents
+        .trim()
+        .split_once(':')
+        .map(|(user, password)| (user.to_string(), password.to_string()))
+        .ok_or_else(|| "Malformed auth cookie".to_string())
+}
+
+impl BitcoindClient {
+    pub fn new(rpc_url: &str, credentials: Option<(String, String)>) -> Result<Self, String> {
+        let headers = match credentials {
+            Some((user, password)) => basic_auth_header(&user, &password)?,
+            None => HeaderMap::new(),
+        };
+        let client = HttpClientBuilder::default()
+            .set_headers(headers)
+            .build(rpc_url)
+            .map_err(|e| e.to_string())?;
+        Ok(Self { client })
+    }
+
+    pub fn estimate_fees(&self) -> Task<ClientResult<FeeEstimates>> {
+        let client = self.client.clone();
+        Task::perform(
+            async move {
+                let mut rates = [0; 3];
+                for (rate, target) in rates.iter_mut().zip([2, 6, 144]) {
+                    let estimate: serde_json::Value = client
+                        .request("estimatesmartfee", rpc_params![target])
+                        .await?;
+                    // BTC/kvB to sat/vB, rounded to whole sats first to drop float noise
+                    *rate = estimate["feerate"]
+                        .as_f64()
+                        .map(|btc_kvb| ((btc_kvb * 100_000_000.0).round() as u64).div_ceil(1000))
+                        .ok_or_else(|| {
+                            ClientError::Custom("Fee estimates are not available yet".to_string())
+                        })?;
+                }
+                Ok(FeeEstimates {
+                    fast: rates[0],
+                    normal: rates[1],
+                    slow: rates[2],
+                })
+            },
+            map_bitcoind_result,
+        )
+    }
+
+    pub fn get_chain(&self) -> Task<ClientResult<String>> {
+        let client = self.client.clone();
+        Task::perform(
+            async move {
+                let info: serde_json::Value =
+                    client.request("getblockchaininfo", rpc_params![]).await?;
+                Ok(info["chain"].as_str().unwrap_or_default().to_string())
+            },
+            map_bitcoind_result,
+        )
+    }
+}
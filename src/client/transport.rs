@@ -0,0 +1,276 @@
+use hyper::{
+    Body, Request, Response, Uri,
+    client::{
+        HttpConnector,
+        connect::{Connected, Connection},
+    },
+};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use jsonrpsee::http_client::transport::{Error, HttpBackend};
+use rustls::{
+    Certificate, ClientConfig, RootCertStore, ServerName,
+    client::{ServerCertVerified, ServerCertVerifier},
+};
+use std::{
+    fs::File,
+    future::Future,
+    io::{self, BufReader},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_socks::tcp::Socks5Stream;
+use tower::{Layer, Service};
+
+use spaces_client::store::Sha256;
+use spaces_protocol::hasher::KeyHasher;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type HttpsClient = hyper::Client<HttpsConnector<Connector>>;
+
+pub enum Stream {
+    Direct(TcpStream),
+    Proxy(Socks5Stream<TcpStream>),
+}
+
+impl Connection for Stream {
+    fn connected(&self) -> Connected {
+        match self {
+            Self::Direct(stream) => stream.connected(),
+            Self::Proxy(_) => Connected::new(),
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Direct(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Proxy(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Direct(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Proxy(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Direct(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Proxy(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Direct(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Proxy(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+fn proxy_error(err: tokio_socks::Error) -> String {
+    match err {
+        tokio_socks::Error::Io(_) | tokio_socks::Error::ProxyServerUnreachable => {
+            format!("Proxy is unreachable: {}", err)
+        }
+        _ => format!("Proxy failed to reach the server: {}", err),
+    }
+}
+
+fn target(uri: &Uri) -> Result<(String, u16), String> {
+    let host = uri
+        .host()
+        .ok_or_else(|| "URL has no host".to_string())?
+        .trim_matches(|c| c == '[' || c == ']')
+        .to_string();
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("https") => 443,
+        _ => 80,
+    });
+    Ok((host, port))
+}
+
+async fn connect_proxy(proxy: &str, uri: &Uri) -> Result<Socks5Stream<TcpStream>, String> {
+    let (host, port) = target(uri)?;
+    // hostnames are resolved by the proxy, which is required for onion services
+    Socks5Stream::connect(proxy, (host.as_str(), port))
+        .await
+        .map_err(proxy_error)
+}
+
+/// Opens a tunnel through the proxy to the RPC host without talking to it,
+/// so proxy problems can be told apart from RPC ones.
+pub async fn check_proxy(proxy: &str, rpc_url: &str) -> Result<(), String> {
+    let uri: Uri = rpc_url.parse().map_err(|e| format!("Invalid URL: {}", e))?;
+    connect_proxy(proxy, &uri).await.map(|_| ())
+}
+
+#[derive(Debug, Clone)]
+pub struct Connector {
+    http: HttpConnector,
+    proxy: Option<String>,
+}
+
+impl Service<Uri> for Connector {
+    type Response = Stream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        match self.proxy.clone() {
+            Some(proxy) => {
+                Box::pin(async move { Ok(Stream::Proxy(connect_proxy(&proxy, &uri).await?)) })
+            }
+            None => {
+                let connecting = self.http.call(uri);
+                Box::pin(async move { Ok(Stream::Direct(connecting.await?)) })
+            }
+        }
+    }
+}
+
+/// Either the stock jsonrpsee backend or our own hyper client when the
+/// connection needs TLS or proxy settings jsonrpsee does not expose.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    Default(HttpBackend),
+    Custom(HttpsClient),
+}
+
+impl Service<Request<Body>> for Backend {
+    type Response = Response<Body>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self {
+            Self::Default(inner) => inner.poll_ready(cx),
+            Self::Custom(inner) => inner.poll_ready(cx).map_err(|e| Error::Http(e.into())),
+        }
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        match self {
+            Self::Default(inner) => inner.call(request),
+            Self::Custom(inner) => {
+                let response = inner.call(request);
+                Box::pin(async move { response.await.map_err(|e| Error::Http(e.into())) })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BackendLayer(Option<HttpsClient>);
+
+impl Layer<HttpBackend> for BackendLayer {
+    type Service = Backend;
+
+    fn layer(&self, inner: HttpBackend) -> Self::Service {
+        match &self.0 {
+            Some(client) => Backend::Custom(client.clone()),
+            None => Backend::Default(inner),
+        }
+    }
+}
+
+struct PinnedCertVerifier([u8; 32]);
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if Sha256::hash(&end_entity.0) == self.0 {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "Certificate fingerprint mismatch".to_string(),
+            ))
+        }
+    }
+}
+
+fn parse_fingerprint(fingerprint: &str) -> Result<[u8; 32], String> {
+    hex::decode(fingerprint.replace(':', ""))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Certificate fingerprint must be a SHA-256 hex string".to_string())
+}
+
+fn load_ca_cert(path: &str) -> Result<RootCertStore, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to read CA certificate: {}", e))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .map_err(|e| format!("Failed to parse CA certificate: {}", e))?;
+    let mut roots = RootCertStore::empty();
+    let (added, _) = roots.add_parsable_certificates(&certs);
+    if added == 0 {
+        return Err("No certificates found in CA file".to_string());
+    }
+    Ok(roots)
+}
+
+impl BackendLayer {
+    pub fn new(ca_cert: &str, fingerprint: &str, proxy: &str) -> Result<Self, String> {
+        if ca_cert.is_empty() && fingerprint.is_empty() && proxy.is_empty() {
+            return Ok(Self(None));
+        }
+        let builder = ClientConfig::builder().with_safe_defaults();
+        let connector = HttpsConnectorBuilder::new();
+        let connector = if !fingerprint.is_empty() {
+            connector.with_tls_config(
+                builder
+                    .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier(
+                        parse_fingerprint(fingerprint)?,
+                    )))
+                    .with_no_client_auth(),
+            )
+        } else if !ca_cert.is_empty() {
+            connector.with_tls_config(
+                builder
+                    .with_root_certificates(load_ca_cert(ca_cert)?)
+                    .with_no_client_auth(),
+            )
+        } else {
+            connector.with_native_roots()
+        };
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        let connector = connector
+            .https_or_http()
+            .enable_http1()
+            .wrap_connector(Connector {
+                http,
+                proxy: (!proxy.is_empty()).then(|| proxy.to_string()),
+            });
+        Ok(Self(Some(hyper::Client::builder().build(connector))))
+    }
+}
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use spaces_client::config::ExtendedNetwork;
+
+use crate::helpers::{is_recipient_on_network, recipient_from_str};
+
+const FILE_NAME: &str = "contacts.json";
+
+/// A labelled bitcoin address or `@space` handle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub label: String,
+    pub recipient: String,
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.label, self.recipient)
+    }
+}
+
+impl Contact {
+    pub fn new(label: &str, recipient: &str) -> Result<Self, String> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Err("Label is empty".to_string());
+        }
+        let recipient = recipient_from_str(recipient.trim())
+            .ok_or_else(|| format!("Invalid recipient {}", recipient.trim()))?;
+        Ok(Self {
+            label: label.to_string(),
+            recipient,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactsFormat {
+    Json,
+    Csv,
+}
+
+impl ContactsFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// Contacts kept in the data dir next to the config.
+#[derive(Debug)]
+pub struct AddressBook {
+    path: PathBuf,
+    contacts: Vec<Contact>,
+    load_error: Option<String>,
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn contacts_from_csv(s: &str) -> Result<Vec<Contact>, String> {
+    let mut contacts = Vec::new();
+    let mut first = true;
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // recipients never contain commas, so labels may
+        let (label, recipient) = line
+            .rsplit_once(',')
+            .ok_or_else(|| format!("Line {}: expected label,recipient", i + 1))?;
+        let label = label.trim();
+        if std::mem::take(&mut first)
+            && label.eq_ignore_ascii_case("label")
+            && recipient.trim().eq_ignore_ascii_case("recipient")
+        {
+            continue;
+        }
+        let label = match label.strip_prefix('"').and_then(|l| l.strip_suffix('"')) {
+            Some(quoted) => quoted.replace("\"\"", "\""),
+            None => label.to_string(),
+        };
+        contacts.push(
+            Contact::new(&label, recipient).map_err(|err| format!("Line {}: {}", i + 1, err))?,
+        );
+    }
+    Ok(contacts)
+}
+
+impl AddressBook {
+    /// An unreadable address book loads empty and is never saved over.
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(FILE_NAME);
+        let result = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(format!("Failed to read {}: {}", path.display(), err)),
+        };
+        match result {
+            Ok(contacts) => Self {
+                path,
+                contacts,
+                load_error: None,
+            },
+            Err(err) => Self {
+                path,
+                contacts: Vec::new(),
+                load_error: Some(err),
+            },
+        }
+    }
+
+    pub fn load_error(&self) -> Option<&String> {
+        self.load_error.as_ref()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(err) = self.load_error.as_ref() {
+            return Err(format!("Address book is not saved: {}", err));
+        }
+        let contents = serde_json::to_string_pretty(&self.contacts).map_err(|e| e.to_string())?;
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, contents)
+            .and_then(|_| fs::rename(&temp, &self.path))
+            .map_err(|e| format!("Failed to save address book: {}", e))
+    }
+
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    /// Contacts that can be paid on `network`, `@space` handles resolve on any of them.
+    pub fn contacts_on_network(&self, network: ExtendedNetwork) -> Vec<Contact> {
+        self.contacts
+            .iter()
+            .filter(|contact| is_recipient_on_network(&contact.recipient, network))
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a contact, replacing the one with the same label.
+    pub fn add(&mut self, contact: Contact) {
+        match self.contacts.iter_mut().find(|c| c.label == contact.label) {
+            Some(existing) => *existing = contact,
+            None => self.contacts.push(contact),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.contacts.len() {
+            self.contacts.remove(index);
+        }
+    }
+
+    /// Adds contacts from a JSON array or `label,recipient` CSV lines.
+    pub fn import(&mut self, contents: &str) -> Result<(), String> {
+        let contacts = if contents.trim_start().starts_with('[') {
+            serde_json::from_str::<Vec<Contact>>(contents)
+                .map_err(|e| format!("Failed to parse JSON: {}", e))?
+                .into_iter()
+                .map(|contact| Contact::new(&contact.label, &contact.recipient))
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            contacts_from_csv(contents)?
+        };
+        if contacts.is_empty() {
+            return Err("No contacts found".to_string());
+        }
+        for contact in contacts {
+            self.add(contact);
+        }
+        Ok(())
+    }
+
+    pub fn export(&self, format: ContactsFormat) -> String {
+        match format {
+            ContactsFormat::Json => serde_json::to_string_pretty(&self.contacts).unwrap(),
+            ContactsFormat::Csv => {
+                self.contacts
+                    .iter()
+                    .fold("label,recipient\n".to_string(), |csv, contact| {
+                        format!(
+                            "{}{},{}\n",
+                            csv,
+                            csv_field(&contact.label),
+                            contact.recipient
+                        )
+                    })
+            }
+        }
+    }
+}
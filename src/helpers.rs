@@ -1,4 +1,8 @@
+use std::str::FromStr;
+
+use spaces_client::config::ExtendedNetwork;
 pub use spaces_protocol::slabel::SLabel;
+use spaces_wallet::bitcoin::{Address, Network, Script, address::NetworkUnchecked};
 pub use spaces_wallet::{
     Listing,
     bitcoin::{Amount, FeeRate},
@@ -17,18 +21,46 @@ pub fn slabel_from_str(s: &str) -> Option<SLabel> {
 
 pub fn is_recipient_input(s: &str) -> bool {
     s.chars()
-        .all(|c| c.is_ascii_digit() || c.is_ascii_lowercase() || c == '-' || c == '@')
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '@')
 }
 
+/// A bitcoin address on any network or an `@space` handle, addresses come back normalized.
 pub fn recipient_from_str(s: &str) -> Option<String> {
-    // TODO: check
-    if s.is_empty() {
-        None
-    } else {
-        Some(s.to_string())
+    match s.strip_prefix('@') {
+        Some(space) => slabel_from_str(space).map(|_| s.to_string()),
+        None => Address::<NetworkUnchecked>::from_str(s)
+            .ok()
+            .map(|address| address.assume_checked().to_string()),
+    }
+}
+
+/// The space an `@space` recipient points to.
+pub fn recipient_slabel(recipient: &str) -> Option<SLabel> {
+    recipient.strip_prefix('@').and_then(slabel_from_str)
+}
+
+pub fn bitcoin_network(network: ExtendedNetwork) -> Network {
+    match network {
+        ExtendedNetwork::Mainnet => Network::Bitcoin,
+        ExtendedNetwork::Testnet | ExtendedNetwork::Testnet4 => Network::Testnet,
+        ExtendedNetwork::Signet => Network::Signet,
+        ExtendedNetwork::Regtest => Network::Regtest,
     }
 }
 
+pub fn script_address(script: &Script, network: ExtendedNetwork) -> Option<String> {
+    Address::from_script(script, bitcoin_network(network))
+        .ok()
+        .map(|address| address.to_string())
+}
+
+/// Whether an address recipient belongs to the network, `@space` handles always do.
+pub fn is_recipient_on_network(recipient: &str, network: ExtendedNetwork) -> bool {
+    recipient.starts_with('@')
+        || Address::<NetworkUnchecked>::from_str(recipient)
+            .is_ok_and(|address| address.is_valid_for_network(bitcoin_network(network)))
+}
+
 pub fn is_amount_input(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_digit())
 }
@@ -49,11 +81,83 @@ pub fn fee_rate_from_str(s: &str) -> Option<Option<FeeRate>> {
     }
 }
 
+/// Largest virtual size an input spending `script_pubkey` adds, for a single key spend with
+/// the longest signature. Scripts the wallet doesn't create get the largest of them.
+fn max_input_vsize(script_pubkey: &Script) -> u64 {
+    if script_pubkey.is_p2tr() {
+        58
+    } else if script_pubkey.is_p2wpkh() {
+        69
+    } else if script_pubkey.is_p2sh() {
+        92
+    } else {
+        149
+    }
+}
+
+fn compact_size_len(n: usize) -> u64 {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+/// Largest virtual size of a transaction spending the given outputs to `outputs` outputs of
+/// any standard type, so a fee worked out from it is never too low.
+pub fn max_vsize<'a>(inputs: impl ExactSizeIterator<Item = &'a Script>, outputs: usize) -> u64 {
+    // version and locktime, the counts and the segwit marker and flag rounded up
+    8 + compact_size_len(inputs.len())
+        + compact_size_len(outputs)
+        + 1
+        + inputs.map(max_input_vsize).sum::<u64>()
+        + 43 * outputs as u64
+}
+
+/// Parses `recipient,amount` lines. The first line may be a `recipient,amount` or
+/// `address,amount` header, any other line that doesn't parse is an error.
+pub fn payments_from_csv(s: &str) -> Result<Vec<(String, Amount)>, String> {
+    let mut payments = Vec::new();
+    let mut first = true;
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (recipient, amount) = line
+            .split_once(',')
+            .ok_or_else(|| format!("Line {}: expected recipient,amount", i + 1))?;
+        let amount = amount.trim();
+        if std::mem::take(&mut first)
+            && matches!(
+                recipient.trim().to_ascii_lowercase().as_str(),
+                "recipient" | "address"
+            )
+            && amount.eq_ignore_ascii_case("amount")
+        {
+            continue;
+        }
+        let recipient = recipient_from_str(recipient.trim())
+            .ok_or_else(|| format!("Line {}: invalid recipient", i + 1))?;
+        let amount =
+            amount_from_str(amount).ok_or_else(|| format!("Line {}: invalid amount", i + 1))?;
+        payments.push((recipient, amount));
+    }
+    if payments.is_empty() {
+        return Err("No payments found".to_string());
+    }
+    Ok(payments)
+}
+
 pub fn listing_from_str(s: &str) -> Option<Listing> {
     serde_json::from_str(s).ok()
 }
 
-pub fn format_amount_number(mut n: u64) -> String {
+/// Formats a number of sats, masked when `hidden` is set for screen-sharing.
+pub fn format_amount_number(mut n: u64, hidden: bool) -> String {
+    if hidden {
+        return "\u{2022}\u{2022}\u{2022}\u{2022}\u{2022} sat".to_string();
+    }
     if n == 0 {
         return "0 sat".to_string();
     }
@@ -78,8 +182,8 @@ pub fn format_amount_number(mut n: u64) -> String {
     result
 }
 
-pub fn format_amount(amount: crate::helpers::Amount) -> String {
-    format_amount_number(amount.to_sat())
+pub fn format_amount(amount: crate::helpers::Amount, hidden: bool) -> String {
+    format_amount_number(amount.to_sat(), hidden)
 }
 
 pub fn height_to_future_est(block_height: u32, tip_height: u32) -> String {
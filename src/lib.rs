@@ -0,0 +1,621 @@
+mod app;
+pub mod client;
+mod contacts;
+pub mod helpers;
+#[cfg(test)]
+mod mock;
+mod pages;
+mod secret;
+mod spaced;
+mod widget;
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use spaces_client::config::ExtendedNetwork;
+
+use client::{BitcoindClient, Client, ClientOptions, read_bitcoind_cookie};
+use secret::MasterKey;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConfigBackend {
+    Embedded {
+        network: ExtendedNetwork,
+    },
+    Bitcoind {
+        network: ExtendedNetwork,
+        url: String,
+        cookie: String,
+        user: String,
+        password: String,
+    },
+    Spaced {
+        network: ExtendedNetwork,
+        url: String,
+        #[serde(default)]
+        user: String,
+        #[serde(default)]
+        password: String,
+        #[serde(default)]
+        token: String,
+        #[serde(default)]
+        ca_cert: String,
+        #[serde(default)]
+        fingerprint: String,
+        #[serde(default)]
+        proxy: String,
+    },
+}
+
+impl ConfigBackend {
+    pub fn network(&self) -> ExtendedNetwork {
+        match self {
+            Self::Embedded { network }
+            | Self::Bitcoind { network, .. }
+            | Self::Spaced { network, .. } => *network,
+        }
+    }
+
+    /// Client for the spaced behind this backend, for the local ones it has to be running already.
+    pub fn connect(&self) -> Result<Client, String> {
+        match self {
+            Self::Embedded { network } | Self::Bitcoind { network, .. } => {
+                Client::new(&spaced::rpc_url(*network), ClientOptions::default())
+            }
+            Self::Spaced {
+                url,
+                user,
+                password,
+                token,
+                ca_cert,
+                fingerprint,
+                proxy,
+                ..
+            } => Client::new(
+                url,
+                ClientOptions {
+                    user,
+                    password,
+                    token,
+                    ca_cert,
+                    fingerprint,
+                    proxy,
+                },
+            ),
+        }
+    }
+
+    /// Client for the bitcoind behind a bitcoind backend.
+    pub fn bitcoind(&self) -> Option<Result<BitcoindClient, String>> {
+        match self {
+            Self::Bitcoind {
+                url,
+                cookie,
+                user,
+                password,
+                ..
+            } => {
+                let credentials = if !cookie.is_empty() {
+                    read_bitcoind_cookie(cookie).map(Some)
+                } else if !user.is_empty() {
+                    Ok(Some((user.clone(), password.clone())))
+                } else {
+                    Ok(None)
+                };
+                Some(credentials.and_then(|credentials| BitcoindClient::new(url, credentials)))
+            }
+            _ => None,
+        }
+    }
+
+    fn secrets_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Self::Embedded { .. } => Vec::new(),
+            Self::Bitcoind { user, password, .. } => vec![user, password],
+            Self::Spaced {
+                user,
+                password,
+                token,
+                ..
+            } => vec![user, password, token],
+        }
+    }
+
+    pub fn default_profile_name(&self) -> String {
+        let kind = match self {
+            Self::Embedded { .. } => "embedded",
+            Self::Bitcoind { .. } => "bitcoind",
+            Self::Spaced { .. } => "spaced",
+        };
+        format!("{} {}", self.network(), kind)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigProfile {
+    pub name: String,
+    pub backend: ConfigBackend,
+    pub wallet: Option<String>,
+}
+
+const CONFIG_VERSION: u64 = 2;
+
+/// Upgrades a config written by an older release to the current schema.
+fn migrate(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "config version {} is newer than this release supports",
+            version
+        ));
+    }
+    if version < 1 {
+        // before profiles only the active backend was stored, it becomes the first profile
+        if let Some(backend) = value.get("backend").filter(|b| !b.is_null()) {
+            let backend: ConfigBackend =
+                serde_json::from_value(backend.clone()).map_err(|e| e.to_string())?;
+            value["profile"] = backend.default_profile_name().into();
+        }
+    }
+    if version < 2 {
+        // the auto-lock timeout and privacy mode came with credential encryption, all start off
+        if let Some(object) = value.as_object_mut() {
+            object
+                .entry("lock_timeout")
+                .or_insert(serde_json::Value::Null);
+            object.entry("hide_amounts").or_insert(false.into());
+        }
+    }
+    value["version"] = CONFIG_VERSION.into();
+    Ok(value)
+}
+
+/// Present when every non-empty credential in the file is encrypted with the master key,
+/// the stored values themselves are never inspected to tell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConfigEncryption {
+    salt: String,
+    /// Empty string encrypted with the master key, to tell a wrong passphrase apart.
+    check: String,
+}
+
+/// Values replaced by session overrides, each with the value saved before it.
+#[derive(Debug, Clone, Default)]
+struct SessionOverrides {
+    backend: Option<(Option<ConfigBackend>, ConfigBackend)>,
+    wallet: Option<(Option<String>, String)>,
+}
+
+fn same_backend(a: &ConfigBackend, b: &ConfigBackend) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    version: u64,
+    #[serde(skip)]
+    path: PathBuf,
+    pub backend: Option<ConfigBackend>,
+    pub wallet: Option<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    profiles: Vec<ConfigProfile>,
+    /// Minutes without input before the wallet locks, only used with a master passphrase.
+    #[serde(default)]
+    pub lock_timeout: Option<u64>,
+    #[serde(default)]
+    pub hide_amounts: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption: Option<ConfigEncryption>,
+    #[serde(skip)]
+    key: Option<MasterKey>,
+    #[serde(skip)]
+    session: SessionOverrides,
+    /// Set when the config file could not be read, so it is never written over.
+    #[serde(skip)]
+    ephemeral: bool,
+    #[serde(skip)]
+    load_error: Option<String>,
+}
+
+fn parse_network(s: &str) -> Result<ExtendedNetwork, String> {
+    [
+        ExtendedNetwork::Mainnet,
+        ExtendedNetwork::Testnet,
+        ExtendedNetwork::Testnet4,
+        ExtendedNetwork::Signet,
+        ExtendedNetwork::Regtest,
+    ]
+    .into_iter()
+    .find(|network| network.to_string() == s)
+    .ok_or_else(|| format!("unknown network {}", s))
+}
+
+/// Session settings taken from the command line or environment, never written to the config.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Overrides {
+    /// Config file to use instead of the default one
+    #[arg(long, global = true, env = "AKRON_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Network to use for this session
+    #[arg(long, global = true, env = "AKRON_NETWORK", value_parser = parse_network)]
+    pub network: Option<ExtendedNetwork>,
+    /// Spaced JSON-RPC URL to use for this session
+    #[arg(long, global = true, env = "AKRON_RPC_URL")]
+    pub rpc_url: Option<String>,
+    /// Wallet to use for this session
+    #[arg(long, global = true, env = "AKRON_WALLET")]
+    pub wallet: Option<String>,
+}
+
+impl Config {
+    pub fn default_path() -> Result<PathBuf, String> {
+        ProjectDirs::from("", "", "akron")
+            .map(|dirs| dirs.data_dir().join("config.json"))
+            .ok_or_else(|| "Failed to find the user's data directory".to_string())
+    }
+
+    fn empty(path: PathBuf) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            path,
+            backend: None,
+            wallet: None,
+            profile: None,
+            profiles: Vec::new(),
+            lock_timeout: None,
+            hide_amounts: false,
+            encryption: None,
+            key: None,
+            session: Default::default(),
+            ephemeral: false,
+            load_error: None,
+        }
+    }
+
+    /// A blank config that is never saved, for when the file can't be reached.
+    fn unsaved(path: PathBuf, err: String) -> Self {
+        let mut config = Self::empty(path);
+        config.ephemeral = true;
+        config.load_error = Some(format!("{}, changes will not be saved", err));
+        config
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        let mut config: Self =
+            serde_json::from_value(migrate(value)?).map_err(|e| e.to_string())?;
+        config.sync_profile();
+        Ok(config)
+    }
+
+    pub fn load(path: PathBuf) -> Self {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::empty(path),
+            // don't overwrite a config we could not even read
+            Err(err) => return Self::unsaved(path, format!("Failed to read config: {}", err)),
+        };
+        match Self::parse(&contents) {
+            Ok(config) => Self { path, ..config },
+            Err(err) => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                let mut backup = path.clone().into_os_string();
+                backup.push(format!(".{}.bak", timestamp));
+                let backup = PathBuf::from(backup);
+                let mut config = Self::empty(path);
+                config.load_error = Some(match fs::rename(&config.path, &backup) {
+                    Ok(()) => format!(
+                        "Config was unreadable ({}), it was moved to {}",
+                        err,
+                        backup.display()
+                    ),
+                    Err(backup_err) => {
+                        config.ephemeral = true;
+                        format!(
+                            "Config was unreadable ({}) and could not be backed up, changes will not be saved: {}",
+                            err, backup_err
+                        )
+                    }
+                });
+                config
+            }
+        }
+    }
+
+    /// Whether changes can't be saved because the config file could not be read.
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    pub fn take_load_error(&mut self) -> Option<String> {
+        self.load_error.take()
+    }
+
+    pub fn load_with(overrides: &Overrides) -> Self {
+        let path = overrides.config.clone().map_or_else(Self::default_path, Ok);
+        let mut config = match path {
+            Ok(path) => match path.parent().map_or(Ok(()), fs::create_dir_all) {
+                Ok(()) => Self::load(path),
+                Err(err) => Self::unsaved(
+                    path,
+                    format!("Failed to create the data directory: {}", err),
+                ),
+            },
+            Err(err) => Self::unsaved(std::env::temp_dir().join("akron").join("config.json"), err),
+        };
+        config.apply(overrides);
+        config
+    }
+
+    /// Applies session overrides, saving writes back the values they replaced.
+    fn apply(&mut self, overrides: &Overrides) {
+        let saved_backend = self.backend.clone();
+        if let Some(url) = overrides.rpc_url.as_ref() {
+            let network = overrides
+                .network
+                .or(self.backend.as_ref().map(ConfigBackend::network))
+                .unwrap_or(ExtendedNetwork::Mainnet);
+            match self.backend.as_mut() {
+                Some(ConfigBackend::Spaced {
+                    url: current,
+                    network: current_network,
+                    ..
+                }) => {
+                    *current = url.clone();
+                    *current_network = network;
+                }
+                _ => {
+                    self.backend = Some(ConfigBackend::Spaced {
+                        network,
+                        url: url.clone(),
+                        user: String::new(),
+                        password: String::new(),
+                        token: String::new(),
+                        ca_cert: String::new(),
+                        fingerprint: String::new(),
+                        proxy: String::new(),
+                    })
+                }
+            }
+        } else if let Some(network) = overrides.network {
+            match self.backend.as_mut() {
+                Some(ConfigBackend::Embedded { network: current })
+                | Some(ConfigBackend::Bitcoind {
+                    network: current, ..
+                })
+                | Some(ConfigBackend::Spaced {
+                    network: current, ..
+                }) => *current = network,
+                None => {}
+            }
+        }
+        if overrides.rpc_url.is_some() || overrides.network.is_some() {
+            if let Some(backend) = self.backend.clone() {
+                self.session.backend = Some((saved_backend, backend));
+            }
+        }
+        if let Some(wallet) = overrides.wallet.as_ref() {
+            self.session.wallet = Some((self.wallet.clone(), wallet.clone()));
+            self.wallet = Some(wallet.clone());
+        }
+    }
+
+    /// Backend and wallet as they are written, overridden values still in use are swapped back.
+    fn persisted(&self) -> (Option<ConfigBackend>, Option<String>) {
+        let backend = match self.session.backend.as_ref() {
+            Some((saved, session))
+                if self
+                    .backend
+                    .as_ref()
+                    .is_some_and(|backend| same_backend(backend, session)) =>
+            {
+                saved.clone()
+            }
+            _ => self.backend.clone(),
+        };
+        let wallet = match self.session.wallet.as_ref() {
+            Some((saved, session)) if self.wallet.as_ref() == Some(session) => saved.clone(),
+            _ => self.wallet.clone(),
+        };
+        (backend, wallet)
+    }
+
+    fn sync_profile(&mut self) {
+        let (backend, wallet) = self.persisted();
+        let (Some(name), Some(backend)) = (self.profile.as_ref(), backend) else {
+            return;
+        };
+        let profile = ConfigProfile {
+            name: name.clone(),
+            backend,
+            wallet,
+        };
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        if self.ephemeral {
+            return Err(
+                "Changes are not saved, the config file could not be read at startup".to_string(),
+            );
+        }
+        self.sync_profile();
+        let mut config = self.clone();
+        (config.backend, config.wallet) = self.persisted();
+        if let Some(key) = self.key.as_ref() {
+            for secret in config.secrets_mut() {
+                if !secret.is_empty() {
+                    *secret = key.encrypt(secret);
+                }
+            }
+        }
+        let config = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        // write next to the config and rename, so a crash never leaves a truncated file
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, config)
+            .and_then(|_| fs::rename(&temp, &self.path))
+            .map_err(|e| format!("Failed to save config: {}", e))
+    }
+
+    fn secrets_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        self.backend
+            .iter_mut()
+            .chain(self.profiles.iter_mut().map(|p| &mut p.backend))
+            .flat_map(ConfigBackend::secrets_mut)
+    }
+
+    /// Credentials stay encrypted in memory until the master passphrase is entered.
+    pub fn is_locked(&self) -> bool {
+        self.encryption.is_some() && self.key.is_none()
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<MasterKey, String> {
+        let encryption = self
+            .encryption
+            .as_ref()
+            .ok_or_else(|| "Config is not encrypted".to_string())?;
+        let key = MasterKey::derive(passphrase, &encryption.salt)?;
+        key.decrypt(&encryption.check)?;
+        Ok(key)
+    }
+
+    /// Checks a passphrase on the blocking pool, deriving the key takes a noticeable moment.
+    pub fn verify_passphrase(
+        &self,
+        passphrase: String,
+    ) -> impl Future<Output = Result<(), String>> + 'static {
+        let config = Self {
+            encryption: self.encryption.clone(),
+            ..Self::empty(PathBuf::new())
+        };
+        async move {
+            tokio::task::spawn_blocking(move || config.derive_key(&passphrase).map(|_| ()))
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    }
+
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), String> {
+        let key = self.derive_key(passphrase)?;
+        let mut secrets = Vec::new();
+        for secret in self.secrets_mut() {
+            secrets.push(if secret.is_empty() {
+                String::new()
+            } else {
+                key.decrypt(secret)?
+            });
+        }
+        for (secret, plaintext) in self.secrets_mut().zip(secrets) {
+            *secret = plaintext;
+        }
+        self.key = Some(key);
+        Ok(())
+    }
+
+    /// Drops the encrypted credentials when the passphrase is lost, everything else is kept.
+    pub fn forget_secrets(&mut self) -> Result<(), String> {
+        if self.is_locked() {
+            for secret in self.secrets_mut() {
+                secret.clear();
+            }
+        }
+        self.encryption = None;
+        self.key = None;
+        self.save()
+    }
+
+    /// Sets a new master passphrase, an empty one stores credentials in plain text again.
+    pub fn set_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        let (encryption, key) = (self.encryption.clone(), self.key.clone());
+        if passphrase.is_empty() {
+            self.encryption = None;
+            self.key = None;
+        } else {
+            let salt = secret::new_salt();
+            let key = MasterKey::derive(passphrase, &salt)?;
+            self.encryption = Some(ConfigEncryption {
+                salt,
+                check: key.encrypt(""),
+            });
+            self.key = Some(key);
+        }
+        let saved = self.save();
+        if saved.is_err() {
+            // the passphrase only changes once it is on disk
+            (self.encryption, self.key) = (encryption, key);
+        }
+        saved
+    }
+
+    pub fn has_passphrase(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    pub fn profiles(&self) -> Vec<&String> {
+        self.profiles.iter().map(|p| &p.name).collect()
+    }
+
+    pub fn select_profile(&mut self, name: &str) {
+        if let Some(profile) = self.profiles.iter().find(|p| p.name == name) {
+            self.profile = Some(profile.name.clone());
+            self.backend = Some(profile.backend.clone());
+            self.wallet = profile.wallet.clone();
+        }
+    }
+
+    /// Names the active profile, replacing any other profile with the same name.
+    pub fn set_profile_name(&mut self, name: String) {
+        if self.profile.as_ref() == Some(&name) {
+            return;
+        }
+        self.profiles.retain(|p| p.name != name);
+        if let Some(current) = self.profile.as_ref() {
+            if let Some(profile) = self.profiles.iter_mut().find(|p| &p.name == current) {
+                profile.name = name.clone();
+            }
+        }
+        self.profile = Some(name);
+    }
+
+    /// Clears the active backend so a new profile can be set up, keeping the saved ones.
+    pub fn new_profile(&mut self) {
+        self.profile = None;
+        self.backend = None;
+        self.wallet = None;
+    }
+
+    pub fn reset(&mut self) -> Result<(), String> {
+        if let Some(name) = self.profile.as_ref() {
+            self.profiles.retain(|p| &p.name != name);
+        }
+        self.new_profile();
+        self.save()
+    }
+}
+
+pub fn run(overrides: Overrides) -> iced::Result {
+    app::State::run(Config::load_with(&overrides))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,254 @@
+//! In-process fake spaced for driving the screens in tests.
+
+use jsonrpsee::{
+    RpcModule,
+    server::{Server, ServerHandle},
+    types::ErrorObjectOwned,
+};
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use spaces_client::config::ExtendedNetwork;
+
+pub use crate::client::outputs;
+use crate::{Config, ConfigBackend};
+
+pub const WALLET: &str = "default";
+pub const ADDRESS: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+pub const TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+const METHODS: &[&str] = &[
+    "getserverinfo",
+    "getspace",
+    "listwallets",
+    "walletcreate",
+    "walletload",
+    "walletexport",
+    "walletimport",
+    "walletgetinfo",
+    "walletgetbalance",
+    "walletlistspaces",
+    "walletlisttransactions",
+    "walletlistunspent",
+    "walletgetnewaddress",
+    "walletsendrequest",
+    "walletbumpfee",
+    "walletbuy",
+    "walletsell",
+    "walletsignevent",
+    // bitcoind, for the fee estimates
+    "estimatesmartfee",
+];
+
+type Calls = Arc<Mutex<Vec<(&'static str, Value)>>>;
+type Responses = Arc<Mutex<HashMap<&'static str, Result<Value, String>>>>;
+
+pub fn tx_response() -> Value {
+    json!({
+        "txid": TXID,
+        "events": [],
+        "error": null,
+        "raw": null,
+    })
+}
+
+pub fn listing() -> Value {
+    json!({
+        "space": "@example",
+        "price": 10000,
+        "seller": ADDRESS,
+        "signature": "00".repeat(64),
+    })
+}
+
+/// A wallet output as `walletlistunspent` returns it, spending to the mock address' script.
+pub fn unspent(vout: u32, sats: u64, is_spaceout: bool, confirmed: bool) -> Value {
+    json!({
+        "outpoint": format!("{}:{}", TXID, vout),
+        "txout": {
+            "value": sats,
+            "script_pubkey": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+        },
+        "keychain": "External",
+        "is_spent": false,
+        "derivation_index": vout,
+        "chain_position": if confirmed {
+            json!({ "Confirmed": {
+                "anchor": {
+                    "block_id": { "height": 90, "hash": "00".repeat(32) },
+                    "confirmation_time": 0,
+                },
+                "transitively": null,
+            } })
+        } else {
+            json!({ "Unconfirmed": { "last_seen": null } })
+        },
+        "space": null,
+        "is_spaceout": is_spaceout,
+    })
+}
+
+fn default_response(method: &str, params: &Value) -> Value {
+    match method {
+        "getserverinfo" => json!({
+            "network": "regtest",
+            "tip": { "hash": "00".repeat(32), "height": 100 },
+            "chain": { "blocks": 100, "headers": 100 },
+            "ready": true,
+            "progress": 1.0,
+        }),
+        "listwallets" => json!([WALLET]),
+        "walletexport" => json!({ "descriptor": "", "blockheight": 0, "label": WALLET }),
+        "walletimport" => json!(null),
+        "walletgetinfo" => json!({
+            "info": {
+                "label": WALLET,
+                "start_block": 0,
+                "tip": 100,
+                "descriptors": [],
+            },
+            "status": "Complete",
+        }),
+        "walletgetbalance" => json!({
+            "balance": 100000,
+            "details": {
+                "immature": 0,
+                "trusted_pending": 0,
+                "untrusted_pending": 0,
+                "confirmed": 100000,
+                "dust": 0,
+            },
+        }),
+        "walletlistspaces" => json!({ "pending": [], "winning": [], "outbid": [], "owned": [] }),
+        "walletlisttransactions" => json!([]),
+        "walletlistunspent" => json!([]),
+        "walletgetnewaddress" => json!(ADDRESS),
+        "walletsendrequest" => json!({ "result": [tx_response()] }),
+        "walletbumpfee" => json!([tx_response()]),
+        "walletbuy" => tx_response(),
+        "walletsell" => listing(),
+        // the signed event is the one we got, the screens only pass it through
+        "walletsignevent" => params[2].clone(),
+        "estimatesmartfee" => match params[0].as_u64() {
+            Some(2) => json!({ "feerate": 0.00012345, "blocks": 2 }),
+            Some(6) => json!({ "feerate": 0.00005, "blocks": 6 }),
+            _ => json!({ "feerate": 0.00001, "blocks": 144 }),
+        },
+        _ => Value::Null,
+    }
+}
+
+/// Directory under the system temp dir, removed with everything in it when dropped.
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "akron-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[derive(Debug)]
+pub struct MockSpaced {
+    url: String,
+    calls: Calls,
+    responses: Responses,
+    dir: TempDir,
+    _handle: ServerHandle,
+}
+
+impl MockSpaced {
+    pub async fn start() -> Self {
+        let calls = Calls::default();
+        let responses = Responses::default();
+        let mut module = RpcModule::new((calls.clone(), responses.clone()));
+        for &method in METHODS {
+            module
+                .register_method(method, move |params, (calls, responses)| {
+                    let params: Value = params.parse().unwrap_or(Value::Null);
+                    calls.lock().unwrap().push((method, params.clone()));
+                    match responses.lock().unwrap().get(method) {
+                        Some(Ok(value)) => Ok(value.clone()),
+                        Some(Err(err)) => Err(ErrorObjectOwned::owned(-1, err, None::<()>)),
+                        None => Ok(default_response(method, &params)),
+                    }
+                })
+                .unwrap();
+        }
+        let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", server.local_addr().unwrap());
+        Self {
+            url,
+            calls,
+            responses,
+            dir: TempDir::new(),
+            _handle: server.start(module),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Config pointing at this server, kept in a directory that goes away with it.
+    pub fn config(&self) -> Config {
+        config(&self.dir, &self.url)
+    }
+
+    pub fn respond(&self, method: &'static str, response: Result<Value, String>) {
+        self.responses.lock().unwrap().insert(method, response);
+    }
+
+    pub fn restore(&self, method: &str) {
+        self.responses.lock().unwrap().remove(method);
+    }
+
+    pub fn calls(&self, method: &str) -> Vec<Value> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(m, _)| *m == method)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+}
+
+pub fn config(dir: &TempDir, url: &str) -> Config {
+    let mut config = Config::load(dir.path().join("config.json"));
+    config.backend = Some(ConfigBackend::Spaced {
+        network: ExtendedNetwork::Regtest,
+        url: url.to_string(),
+        user: String::new(),
+        password: String::new(),
+        token: String::new(),
+        ca_cert: String::new(),
+        fingerprint: String::new(),
+        proxy: String::new(),
+    });
+    config
+}
@@ -0,0 +1,125 @@
+use iced::{
+    Center, Element, Fill, FillPortion,
+    widget::{
+        Column, Space, button, center, column, container, horizontal_rule, row, scrollable, text,
+    },
+};
+
+use crate::{
+    client::*,
+    helpers::*,
+    widget::{
+        icon::{Icon, button_icon},
+        text::{text_big, text_monospace, text_small},
+    },
+};
+
+#[derive(Debug, Default)]
+pub struct State;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    CopyOutpointPress(OutPoint),
+    SpacePress(SLabel),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    WriteClipboard(String),
+    ShowSpace { slabel: SLabel },
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::CopyOutpointPress(outpoint) => Action::WriteClipboard(outpoint.to_string()),
+            Message::SpacePress(slabel) => Action::ShowSpace { slabel },
+        }
+    }
+
+    pub fn view<'a>(
+        &self,
+        tip_height: u32,
+        unspent: &'a [WalletOutput],
+        hide_amounts: bool,
+    ) -> Element<'a, Message> {
+        let total = unspent
+            .iter()
+            .filter(|output| !output.is_spaceout)
+            .fold(Amount::ZERO, |sum, output| sum + output.output.txout.value);
+        column![
+            column![
+                text_big("Coins"),
+                text(format!(
+                    "{} outputs, {} spendable",
+                    unspent.len(),
+                    format_amount(total, hide_amounts)
+                )),
+                text_small("Spaced picks which coins a transaction spends"),
+            ]
+            .spacing(10)
+            .width(Fill)
+            .align_x(Center),
+            if unspent.is_empty() {
+                Element::from(center(text("No unspent outputs")))
+            } else {
+                scrollable(
+                    Column::from_iter(unspent.iter().map(|output| {
+                        let outpoint = output.output.outpoint;
+                        let outpoint_string = outpoint.to_string();
+                        let confirmations = match output
+                            .output
+                            .chain_position
+                            .confirmation_height_upper_bound()
+                        {
+                            Some(height) if height <= tip_height => {
+                                format!("{} confirmations", tip_height - height + 1)
+                            }
+                            Some(_) => "1 confirmation".to_string(),
+                            None => "Unconfirmed".to_string(),
+                        };
+                        let holding: Element<'a, Message> = match output.space.as_ref() {
+                            Some(space) => button(text_monospace(space.name.to_string()))
+                                .on_press(Message::SpacePress(space.name.clone()))
+                                .style(button::text)
+                                .padding(0)
+                                .into(),
+                            None if output.is_spaceout => text_small("Space output").into(),
+                            None => Space::with_width(Fill).into(),
+                        };
+                        column![
+                            horizontal_rule(2.0),
+                            row![
+                                row![
+                                    text_monospace(format!(
+                                        "{} .. {}",
+                                        &outpoint_string[..8],
+                                        &outpoint_string[outpoint_string.len() - 8..]
+                                    )),
+                                    button_icon(Icon::Copy)
+                                        .on_press(Message::CopyOutpointPress(outpoint)),
+                                ]
+                                .spacing(5)
+                                .align_y(Center)
+                                .width(FillPortion(3)),
+                                container(holding).width(FillPortion(2)),
+                                text_small(confirmations).width(FillPortion(2)),
+                                text(format_amount(output.output.txout.value, hide_amounts))
+                                    .width(FillPortion(2)),
+                            ]
+                            .spacing(10)
+                            .align_y(Center),
+                        ]
+                        .spacing(10)
+                    }))
+                    .spacing(10)
+                    .padding([0, 20]),
+                )
+                .into()
+            },
+        ]
+        .spacing(20)
+        .padding([60, 100])
+        .into()
+    }
+}
@@ -0,0 +1,196 @@
+use iced::{
+    Center, Element, Fill,
+    widget::{Column, button, center, column, row, text},
+};
+
+use crate::{
+    client::*,
+    helpers::*,
+    widget::{
+        form::Form,
+        text::{error_block, text_big, text_monospace, text_small},
+    },
+};
+
+#[derive(Debug, Default)]
+pub struct State {
+    /// Wallet the queued requests are spent from.
+    wallet: Option<String>,
+    requests: Vec<TxRequest>,
+    fee_rate: String,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    RemovePress(usize),
+    ClearPress,
+    FeeRateInput(String),
+    Submit,
+    ClientResult(Result<(), String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Send {
+        requests: Vec<TxRequest>,
+        fee_rate: Option<FeeRate>,
+    },
+    ShowTransactions,
+}
+
+pub fn describe(request: &TxRequest, hide_amounts: bool) -> String {
+    match request {
+        TxRequest::SendCoins { to, amount } => {
+            format!("Send {} to {}", format_amount(*amount, hide_amounts), to)
+        }
+        TxRequest::Open { slabel, amount } => {
+            format!(
+                "Open {} with {}",
+                slabel,
+                format_amount(*amount, hide_amounts)
+            )
+        }
+        TxRequest::Bid { slabel, amount } => {
+            format!("Bid {} on {}", format_amount(*amount, hide_amounts), slabel)
+        }
+        TxRequest::Register { slabel } => format!("Register {}", slabel),
+        TxRequest::Renew { slabels } => format!(
+            "Renew {}",
+            slabels
+                .iter()
+                .map(SLabel::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TxRequest::Transfer { slabels, to } => format!(
+            "Transfer {} to {}",
+            slabels
+                .iter()
+                .map(SLabel::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            to
+        ),
+    }
+}
+
+impl State {
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Drops the queue when it was composed for another wallet.
+    pub fn set_wallet(&mut self, wallet: &str) {
+        if self.wallet.as_deref() != Some(wallet) {
+            self.wallet = Some(wallet.to_string());
+            self.requests.clear();
+            self.fee_rate = Default::default();
+        }
+    }
+
+    pub fn push(&mut self, wallet: &str, request: TxRequest) {
+        self.set_wallet(wallet);
+        self.requests.push(request);
+    }
+
+    pub fn update(&mut self, message: Message) -> Action {
+        self.error = None;
+        match message {
+            Message::RemovePress(index) => {
+                self.requests.remove(index);
+                Action::None
+            }
+            Message::ClearPress => {
+                self.requests.clear();
+                Action::None
+            }
+            Message::FeeRateInput(fee_rate) => {
+                if is_fee_rate_input(&fee_rate) {
+                    self.fee_rate = fee_rate
+                }
+                Action::None
+            }
+            Message::Submit => Action::Send {
+                requests: self.requests.clone(),
+                fee_rate: fee_rate_from_str(&self.fee_rate).unwrap(),
+            },
+            Message::ClientResult(Ok(())) => {
+                self.requests.clear();
+                self.fee_rate = Default::default();
+                Action::ShowTransactions
+            }
+            Message::ClientResult(Err(err)) => {
+                self.error = Some(err);
+                Action::None
+            }
+        }
+    }
+
+    pub fn view(
+        &self,
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
+    ) -> Element<'_, Message> {
+        if self.requests.is_empty() {
+            return center(text(
+                "Use \"Add to transaction\" on the Send and Spaces screens to queue actions",
+            ))
+            .into();
+        }
+        let total = self
+            .requests
+            .iter()
+            .filter_map(|request| match request {
+                TxRequest::SendCoins { amount, .. }
+                | TxRequest::Open { amount, .. }
+                | TxRequest::Bid { amount, .. } => Some(*amount),
+                _ => None,
+            })
+            .fold(Amount::ZERO, |sum, amount| sum + amount);
+        column![
+            row![
+                text_big("Transaction").width(Fill),
+                button("Clear")
+                    .style(button::text)
+                    .on_press(Message::ClearPress),
+            ]
+            .align_y(Center),
+            text_small(format!(
+                "Funded by wallet {}",
+                self.wallet.as_deref().unwrap_or_default()
+            )),
+            error_block(self.error.as_ref()),
+            Column::from_iter(self.requests.iter().enumerate().map(|(index, request)| {
+                row![
+                    text_monospace(describe(request, hide_amounts)).width(Fill),
+                    button("Remove")
+                        .style(button::text)
+                        .on_press(Message::RemovePress(index)),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            }))
+            .spacing(5),
+            text(format!(
+                "{} actions, {} plus fee",
+                self.requests.len(),
+                format_amount(total, hide_amounts)
+            )),
+            Form::new(
+                "Submit",
+                fee_rate_from_str(&self.fee_rate).map(|_| Message::Submit),
+            )
+            .add_fee_rate_input(
+                &self.fee_rate,
+                fee_estimates.map(FeeEstimates::rates),
+                Message::FeeRateInput
+            ),
+        ]
+        .spacing(10)
+        .padding([60, 100])
+        .into()
+    }
+}
@@ -0,0 +1,180 @@
+use iced::{
+    Center, Element, Fill,
+    widget::{Column, button, column, row, scrollable, text},
+};
+
+use super::{composer::describe, state::SpacesCollection};
+use crate::{
+    client::*,
+    helpers::*,
+    widget::text::{text_big, text_bold, text_monospace, text_small},
+};
+
+#[derive(Debug, Default)]
+pub struct State;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    CancelPress,
+    ConfirmPress,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    Cancel,
+    Confirm,
+}
+
+/// What the transaction is asked to do, not a dry run of it. Spaced builds and broadcasts in
+/// one call, so inputs, change and the final fee are only known afterwards and are not shown.
+struct Summary {
+    changes: Vec<String>,
+    payments: Vec<(String, Amount)>,
+    fee_rate: Option<FeeRate>,
+}
+
+impl Summary {
+    fn new(
+        pending: &PendingTx,
+        tip_height: u32,
+        spaces: &SpacesCollection,
+        hide_amounts: bool,
+    ) -> Self {
+        let mut changes = Vec::new();
+        let mut payments = Vec::new();
+        let fee_rate = match pending {
+            PendingTx::Send {
+                requests, fee_rate, ..
+            } => {
+                for request in requests {
+                    match request {
+                        TxRequest::SendCoins { to, amount } => {
+                            payments.push((to.clone(), *amount));
+                        }
+                        TxRequest::Open { slabel, amount } | TxRequest::Bid { slabel, amount } => {
+                            changes.push(match spaces.get_covenant(slabel) {
+                                Some(Some(Covenant::Bid { total_burned, .. })) => format!(
+                                    "{}: bid raised from {} to {}",
+                                    slabel,
+                                    format_amount(*total_burned, hide_amounts),
+                                    format_amount(*amount, hide_amounts)
+                                ),
+                                _ => describe(request, hide_amounts),
+                            });
+                        }
+                        TxRequest::Register { slabel } => {
+                            changes.push(format!("{}: registered to this wallet", slabel));
+                        }
+                        TxRequest::Renew { slabels } => {
+                            for slabel in slabels {
+                                changes.push(match spaces.get_covenant(slabel) {
+                                    Some(Some(Covenant::Transfer { expire_height, .. })) => {
+                                        format!(
+                                            "{}: renewed, currently expires {}",
+                                            slabel,
+                                            height_to_future_est(*expire_height, tip_height)
+                                        )
+                                    }
+                                    _ => format!("{}: renewed", slabel),
+                                });
+                            }
+                        }
+                        TxRequest::Transfer { slabels, to } => {
+                            for slabel in slabels {
+                                changes.push(format!("{}: ownership moves to {}", slabel, to));
+                            }
+                        }
+                    }
+                }
+                *fee_rate
+            }
+            PendingTx::Buy { listing, fee_rate } => {
+                let price = Amount::from_sat(listing.price);
+                changes.push(format!("{}: bought from {}", listing.space, listing.seller));
+                payments.push((listing.seller.clone(), price));
+                *fee_rate
+            }
+        };
+        Self {
+            changes,
+            payments,
+            fee_rate,
+        }
+    }
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::CancelPress => Action::Cancel,
+            Message::ConfirmPress => Action::Confirm,
+        }
+    }
+
+    pub fn view<'a>(
+        &self,
+        pending: &PendingTx,
+        tip_height: u32,
+        spaces: &SpacesCollection,
+        hide_amounts: bool,
+    ) -> Element<'a, Message> {
+        let summary = Summary::new(pending, tip_height, spaces, hide_amounts);
+        let total = summary
+            .payments
+            .iter()
+            .fold(Amount::ZERO, |sum, (_, amount)| sum + *amount);
+        column![
+            text_big("Confirm transaction"),
+            scrollable(
+                column![
+                    Column::new()
+                        .push_maybe((!summary.changes.is_empty()).then(|| text_bold("Spaces")))
+                        .extend(
+                            summary
+                                .changes
+                                .into_iter()
+                                .map(|change| text(change).into())
+                        )
+                        .spacing(5),
+                    Column::new()
+                        .push_maybe((!summary.payments.is_empty()).then(|| text_bold("Payments")))
+                        .extend(summary.payments.into_iter().map(|(to, amount)| {
+                            row![
+                                text_monospace(to).width(Fill),
+                                text(format_amount(amount, hide_amounts)),
+                            ]
+                            .spacing(10)
+                            .into()
+                        }))
+                        .push_maybe((total > Amount::ZERO).then(|| {
+                            text(format!("{} in total", format_amount(total, hide_amounts)))
+                        }))
+                        .spacing(5),
+                    text(match summary.fee_rate {
+                        Some(fee_rate) => {
+                            format!("Fee rate {} sat/vB", fee_rate.to_sat_per_vb_ceil())
+                        }
+                        None => "Fee rate is picked by the node".to_string(),
+                    }),
+                ]
+                .spacing(20)
+            )
+            .height(Fill),
+            text_small(
+                "The node picks the inputs and change and sets the final fee \
+                 when it builds the transaction"
+            ),
+            row![
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::CancelPress),
+                button("Confirm and broadcast").on_press(Message::ConfirmPress),
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .spacing(10)
+        .padding([60, 100])
+        .into()
+    }
+}
@@ -0,0 +1,146 @@
+use iced::{
+    Center, Element, Fill,
+    widget::{Column, button, center, column, horizontal_rule, row, scrollable, text},
+};
+
+use crate::{
+    contacts::{Contact, ContactsFormat},
+    helpers::*,
+    widget::{
+        form::Form,
+        icon::{Icon, button_icon},
+        text::{error_block, text_big, text_monospace},
+    },
+};
+
+#[derive(Debug, Default)]
+pub struct State {
+    label: String,
+    recipient: String,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    LabelInput(String),
+    RecipientInput(String),
+    AddSubmit,
+    RemovePress(usize),
+    CopyPress(String),
+    ImportPress,
+    ExportPress(ContactsFormat),
+    FileLoaded(Result<Option<String>, String>),
+    Saved(Result<(), String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Add(Contact),
+    Remove(usize),
+    WriteClipboard(String),
+    FilePick,
+    Import(String),
+    Export(ContactsFormat),
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        self.error = None;
+        match message {
+            Message::LabelInput(label) => {
+                self.label = label;
+                Action::None
+            }
+            Message::RecipientInput(recipient) => {
+                if is_recipient_input(&recipient) {
+                    self.recipient = recipient;
+                }
+                Action::None
+            }
+            Message::AddSubmit => match Contact::new(&self.label, &self.recipient) {
+                Ok(contact) => {
+                    self.label = Default::default();
+                    self.recipient = Default::default();
+                    Action::Add(contact)
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    Action::None
+                }
+            },
+            Message::RemovePress(index) => Action::Remove(index),
+            Message::CopyPress(recipient) => Action::WriteClipboard(recipient),
+            Message::ImportPress => Action::FilePick,
+            Message::ExportPress(format) => Action::Export(format),
+            Message::FileLoaded(Ok(Some(contents))) => Action::Import(contents),
+            Message::FileLoaded(Ok(None)) | Message::Saved(Ok(())) => Action::None,
+            Message::FileLoaded(Err(err)) | Message::Saved(Err(err)) => {
+                self.error = Some(err);
+                Action::None
+            }
+        }
+    }
+
+    pub fn view<'a>(&'a self, contacts: &'a [Contact]) -> Element<'a, Message> {
+        column![
+            row![
+                text_big("Address book").width(Fill),
+                button("Import")
+                    .style(button::text)
+                    .on_press(Message::ImportPress),
+                button("Export JSON").style(button::text).on_press_maybe(
+                    (!contacts.is_empty()).then_some(Message::ExportPress(ContactsFormat::Json))
+                ),
+                button("Export CSV").style(button::text).on_press_maybe(
+                    (!contacts.is_empty()).then_some(Message::ExportPress(ContactsFormat::Csv))
+                ),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            error_block(self.error.as_ref()),
+            if contacts.is_empty() {
+                Element::from(center(text("No contacts yet")).height(Fill))
+            } else {
+                scrollable(
+                    Column::from_iter(contacts.iter().enumerate().map(|(index, contact)| {
+                        column![
+                            horizontal_rule(2.0),
+                            row![
+                                text(&contact.label).width(Fill),
+                                text_monospace(&contact.recipient),
+                                button_icon(Icon::Copy)
+                                    .on_press(Message::CopyPress(contact.recipient.clone())),
+                                button("Remove")
+                                    .style(button::text)
+                                    .on_press(Message::RemovePress(index)),
+                            ]
+                            .spacing(10)
+                            .align_y(Center),
+                        ]
+                        .spacing(10)
+                        .into()
+                    }))
+                    .spacing(10),
+                )
+                .height(Fill)
+                .into()
+            },
+            Form::new(
+                "Add",
+                (!self.label.trim().is_empty() && recipient_from_str(&self.recipient).is_some())
+                    .then_some(Message::AddSubmit),
+            )
+            .add_text_input("Label", "name", &self.label, Message::LabelInput)
+            .add_text_input(
+                "Address",
+                "bitcoin address or @space",
+                &self.recipient,
+                Message::RecipientInput,
+            ),
+        ]
+        .spacing(20)
+        .padding([60, 100])
+        .into()
+    }
+}
@@ -59,6 +59,44 @@ pub enum Action {
     BumpFee { txid: Txid, fee_rate: FeeRate },
 }
 
+fn balance_view<'a>(
+    balance: Option<&'a Balance>,
+    bids: Amount,
+    hide_amounts: bool,
+) -> Element<'a, Message> {
+    let Some(balance) = balance else {
+        return column![text_big("Balance"), text("Loading")]
+            .padding([30, 0])
+            .spacing(10)
+            .width(Fill)
+            .align_x(Center)
+            .into();
+    };
+    let details = &balance.details.balance;
+    let entry = |label: &'a str, amount: Amount| {
+        column![text_small(label), text(format_amount(amount, hide_amounts))]
+            .spacing(5)
+            .width(Fill)
+            .align_x(Center)
+    };
+    column![
+        text_big("Balance"),
+        text(format_amount(balance.balance, hide_amounts)).size(20),
+        row![
+            entry("Spendable", details.confirmed + details.trusted_pending),
+            entry("Unconfirmed", details.untrusted_pending + details.immature),
+            entry("Locked in spaces", balance.details.dust),
+            entry("Committed to bids", bids),
+        ]
+        .spacing(20),
+    ]
+    .padding([30, 0])
+    .spacing(10)
+    .width(Fill)
+    .align_x(Center)
+    .into()
+}
+
 impl State {
     pub fn reset_inputs(&mut self) {
         self.fee_rate = String::new();
@@ -69,6 +107,11 @@ impl State {
         self.reset_inputs();
     }
 
+    pub fn set_txid(&mut self, txid: Txid) {
+        self.reset_inputs();
+        self.txid = Some(txid);
+    }
+
     pub fn get_transactions_limit(&self) -> usize {
         self.transactions_limit
     }
@@ -118,8 +161,10 @@ impl State {
     pub fn view<'a>(
         &'a self,
         tip_height: u32,
-        balance: Amount,
+        balance: Option<&'a Balance>,
+        bids: Amount,
         transactions: &'a [TxInfo],
+        hide_amounts: bool,
     ) -> Element<'a, Message> {
         if let Some(txid) = self.txid.as_ref() {
             if let Some(transaction) = transactions.iter().find(|tx| &tx.txid == txid) {
@@ -135,7 +180,7 @@ impl State {
                             .style(button::text)
                             .padding(0)
                     ]
-                    .push_maybe(amount.map(|amount| text(format_amount(amount))))
+                    .push_maybe(amount.map(|amount| text(format_amount(amount, hide_amounts))))
                 };
 
                 let event_row_with_string = |action: &'static str, s: String| -> Row<'a, Message> {
@@ -226,6 +271,7 @@ impl State {
                                 SendEventDetails::deserialize(event.details.as_ref().unwrap())
                                     .unwrap()
                                     .amount,
+                                hide_amounts,
                             ),
                         )),
                         TxEvent {
@@ -258,14 +304,18 @@ impl State {
                     row![
                         column![
                             text_bold("Info"),
-                            text(format!("Sent: {}", format_amount(transaction.sent))),
-                            text(format!("Received: {}", format_amount(transaction.received))),
+                            text(format!(
+                                "Sent: {}",
+                                format_amount(transaction.sent, hide_amounts)
+                            )),
+                            text(format!(
+                                "Received: {}",
+                                format_amount(transaction.received, hide_amounts)
+                            )),
                         ]
-                        .push_maybe(
-                            transaction
-                                .fee
-                                .map(|fee| { text(format!("Fee: {}", format_amount(fee))) })
-                        )
+                        .push_maybe(transaction.fee.map(|fee| {
+                            text(format!("Fee: {}", format_amount(fee, hide_amounts)))
+                        }))
                         .push_maybe(transaction.block_height.map(|block_height| text(format!(
                             "Block: {} ({})",
                             block_height,
@@ -312,11 +362,7 @@ impl State {
             }
         } else {
             column![
-                column![text_big("Balance"), text(format_amount(balance)),]
-                    .padding([30, 0])
-                    .spacing(10)
-                    .width(Fill)
-                    .align_x(Center),
+                balance_view(balance, bids, hide_amounts),
                 column![text_big("Transactions"), {
                     let element: Element<'a, Message> = if transactions.is_empty() {
                         center(text("No transactions yet")).into()
@@ -341,8 +387,12 @@ impl State {
                                     row![
                                         horizontal_space(),
                                         if diff >= 0 {
-                                            text(format!("+{}", format_amount_number(diff as u64)))
-                                                .style(move |theme: &Theme| text::Style {
+                                            text(format!(
+                                                "+{}",
+                                                format_amount_number(diff as u64, hide_amounts)
+                                            ))
+                                            .style(
+                                                move |theme: &Theme| text::Style {
                                                     color: Some(
                                                         theme
                                                             .extended_palette()
@@ -350,10 +400,15 @@ impl State {
                                                             .strong
                                                             .color,
                                                     ),
-                                                })
+                                                },
+                                            )
                                         } else {
-                                            text(format!("-{}", format_amount_number(-diff as u64)))
-                                                .style(move |theme: &Theme| text::Style {
+                                            text(format!(
+                                                "-{}",
+                                                format_amount_number(-diff as u64, hide_amounts)
+                                            ))
+                                            .style(
+                                                move |theme: &Theme| text::Style {
                                                     color: Some(
                                                         theme
                                                             .extended_palette()
@@ -361,7 +416,8 @@ impl State {
                                                             .strong
                                                             .color,
                                                     ),
-                                                })
+                                                },
+                                            )
                                         }
                                     ]
                                 };
@@ -380,9 +436,9 @@ impl State {
                                                 .padding(0),
                                             horizontal_space()
                                         ]
-                                        .push_maybe(
-                                            amount.map(|amount| text(format_amount(amount))),
-                                        )
+                                        .push_maybe(amount.map(|amount| {
+                                            text(format_amount(amount, hide_amounts))
+                                        }))
                                         .spacing(5)
                                         .align_y(Center)
                                     };
@@ -0,0 +1,80 @@
+use iced::{
+    Element, Fill,
+    widget::{center, column},
+};
+
+use crate::widget::{
+    form::Form,
+    text::{error_block, text_big},
+};
+
+#[derive(Debug, Default)]
+pub struct State {
+    passphrase: String,
+    checking: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PassphraseInput(String),
+    UnlockSubmit,
+    UnlockResult(Result<(), String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Unlock(String),
+    Unlocked,
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        self.error = None;
+        match message {
+            Message::PassphraseInput(passphrase) => {
+                self.passphrase = passphrase;
+                Action::None
+            }
+            Message::UnlockSubmit => {
+                self.checking = true;
+                Action::Unlock(std::mem::take(&mut self.passphrase))
+            }
+            Message::UnlockResult(result) => {
+                self.checking = false;
+                match result {
+                    Ok(()) => Action::Unlocked,
+                    Err(err) => {
+                        self.error = Some(err);
+                        Action::None
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        center(
+            column![
+                text_big("Wallet is locked"),
+                error_block(self.error.as_ref()),
+                Form::new(
+                    "Unlock",
+                    (!self.checking && !self.passphrase.is_empty())
+                        .then_some(Message::UnlockSubmit),
+                )
+                .add_secure_input(
+                    "Master passphrase",
+                    "",
+                    &self.passphrase,
+                    Message::PassphraseInput,
+                ),
+            ]
+            .spacing(10)
+            .max_width(400)
+            .width(Fill),
+        )
+        .into()
+    }
+}
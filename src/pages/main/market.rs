@@ -156,7 +156,12 @@ impl State {
         }
     }
 
-    pub fn view<'a>(&'a self, owned_spaces: &'a Vec<SLabel>) -> Element<'a, Message> {
+    pub fn view<'a>(
+        &'a self,
+        owned_spaces: &'a Vec<SLabel>,
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
+    ) -> Element<'a, Message> {
         column![
             TabsRow::new()
                 .add_tab("Buy", matches!(self, Self::Buy(_)), Message::BuyTabPress,)
@@ -173,11 +178,10 @@ impl State {
                             .then_some(Message::BuySubmit),
                         )
                         .add_text_editor("Listing", "JSON", &state.listing, Message::ListingAction)
-                        .add_text_input(
-                            "Fee rate",
-                            "sat/vB (auto if empty)",
+                        .add_fee_rate_input(
                             &state.fee_rate,
-                            Message::FeeRateInput,
+                            fee_estimates.map(FeeEstimates::rates),
+                            Message::FeeRateInput
                         )
                     ]
                 }
@@ -205,7 +209,12 @@ impl State {
                     ]
                     .push_maybe(state.listing.as_ref().map(|listing| {
                         container(row![
-                            text_monospace(listing).width(Fill),
+                            text_monospace(if hide_amounts {
+                                "Listing hidden, copy it to view"
+                            } else {
+                                listing
+                            })
+                            .width(Fill),
                             button_icon(Icon::Copy).on_press(Message::CopyPress)
                         ])
                         .style(|theme: &Theme| {
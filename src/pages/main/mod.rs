@@ -1,5 +1,11 @@
+mod coins;
+mod composer;
+mod confirm;
+mod contacts;
 mod home;
+mod lock;
 mod market;
+mod node;
 mod receive;
 mod send;
 mod settings;
@@ -8,16 +14,20 @@ mod spaces;
 mod state;
 
 use iced::{
-    Center, Element, Fill, Subscription, Task, Theme, clipboard, time,
+    Center, Element, Event, Fill, Subscription, Task, Theme, clipboard, event, mouse, time,
     widget::{
         Column, Stack, button, center, column, container, progress_bar, row, text, vertical_rule,
         vertical_space,
     },
 };
 
+use spaces_client::config::ExtendedNetwork;
+
 use crate::{
     Config,
     client::*,
+    contacts::AddressBook,
+    spaced::Spaced,
     widget::{
         icon::{Icon, text_icon},
         text::text_bold,
@@ -29,27 +39,54 @@ enum Screen {
     Home,
     Send,
     Receive,
+    Coins,
     Spaces,
     Market,
     Sign,
+    Contacts,
+    Composer,
+    Node,
     Settings,
 }
 
+/// A transaction on the confirmation screen and the screen its result goes back to.
+#[derive(Debug)]
+struct Pending {
+    tx: PendingTx,
+    on_result: fn(Result<Option<Txid>, String>) -> Message,
+}
+
 #[derive(Debug)]
 pub struct State {
     config: Config,
     client: Client,
+    network: ExtendedNetwork,
+    bitcoind: Option<BitcoindClient>,
+    node: Option<Spaced>,
     screen: Screen,
+    connection: state::Connection,
+    locked: bool,
+    last_activity: time::Instant,
+    wallets_loaded: bool,
     tip_height: u32,
+    fee_estimates: Option<FeeEstimates>,
     wallets: state::WalletsCollection,
     spaces: state::SpacesCollection,
+    address_book: AddressBook,
+    pending: Option<Pending>,
     home_screen: home::State,
     send_screen: send::State,
     receive_screen: receive::State,
+    coins_screen: coins::State,
     spaces_screen: spaces::State,
     market_screen: market::State,
     sign_screen: sign::State,
+    contacts_screen: contacts::State,
+    composer_screen: composer::State,
+    confirm_screen: confirm::State,
+    node_screen: node::State,
     settings_screen: settings::State,
+    lock_screen: lock::State,
 }
 
 #[derive(Debug, Clone)]
@@ -57,33 +94,47 @@ pub enum Route {
     Home,
     Send,
     Receive,
+    Coins,
     Spaces,
     Space(SLabel),
     Market,
     Sign,
+    Contacts,
+    Composer,
+    Node,
     Settings,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Tick,
+    Activity,
+    LockCheck,
     NavigateTo(Route),
     ServerInfo(ClientResult<ServerInfo>),
-    ListWallets(ClientResult<Vec<String>>),
-    WalletLoad(WalletResult<()>),
-    WalletInfo(WalletResult<WalletInfoWithProgress>),
-    WalletBalance(WalletResult<Balance>),
-    WalletSpaces(WalletResult<ListSpacesResponse>),
-    WalletTransactions(WalletResult<Vec<TxInfo>>),
-    WalletAddress(WalletResult<(AddressKind, String)>),
-    SpaceInfo(ClientResult<(SLabel, Option<FullSpaceOut>)>),
+    FeeEstimates(ClientResult<FeeEstimates>),
+    ListWallets(RequestResult<Vec<String>>),
+    WalletLoad(WalletResult<(), RequestError>),
+    WalletInfo(WalletResult<WalletInfoWithProgress, RequestError>),
+    WalletBalance(WalletResult<Balance, RequestError>),
+    WalletSpaces(WalletResult<ListSpacesResponse, RequestError>),
+    WalletTransactions(WalletResult<Vec<TxInfo>, RequestError>),
+    WalletUnspent(WalletResult<Vec<WalletOutput>, RequestError>),
+    WalletAddress(WalletResult<(AddressKind, String), RequestError>),
+    SpaceInfo(RequestResult<(SLabel, Option<FullSpaceOut>)>),
     HomeScreen(home::Message),
     SendScreen(send::Message),
     ReceiveScreen(receive::Message),
+    CoinsScreen(coins::Message),
     SpacesScreen(spaces::Message),
     MarketScreen(market::Message),
     SignScreen(sign::Message),
+    ContactsScreen(contacts::Message),
+    ComposerScreen(composer::Message),
+    ConfirmScreen(confirm::Message),
+    NodeScreen(node::Message),
     SettingsScreen(settings::Message),
+    LockScreen(lock::Message),
 }
 
 pub enum Action {
@@ -92,30 +143,138 @@ pub enum Action {
 }
 
 impl State {
-    pub fn run(config: Config, client: Client) -> (Self, Task<Message>) {
+    pub fn run(config: Config, client: Client, node: Option<Spaced>) -> (Self, Task<Message>) {
+        let bitcoind = config
+            .backend
+            .as_ref()
+            .and_then(|backend| backend.bitcoind())
+            .and_then(Result::ok);
+        let network = config
+            .backend
+            .as_ref()
+            .map_or(ExtendedNetwork::Mainnet, |backend| backend.network());
+        let address_book = AddressBook::load(config.data_dir());
         let state = Self {
             config,
             client,
+            network,
+            bitcoind,
+            node,
             screen: Screen::Home,
+            connection: Default::default(),
+            locked: false,
+            last_activity: time::Instant::now(),
+            wallets_loaded: false,
             tip_height: 0,
+            fee_estimates: None,
             wallets: Default::default(),
             spaces: Default::default(),
+            address_book,
+            pending: None,
             home_screen: Default::default(),
             send_screen: Default::default(),
             receive_screen: Default::default(),
+            coins_screen: Default::default(),
             spaces_screen: Default::default(),
             market_screen: Default::default(),
             sign_screen: Default::default(),
+            contacts_screen: Default::default(),
+            composer_screen: Default::default(),
+            confirm_screen: Default::default(),
+            node_screen: Default::default(),
             settings_screen: Default::default(),
+            lock_screen: Default::default(),
         };
-        let task = Task::batch([state.get_server_info(), state.list_wallets()]);
+        let task = Task::batch([
+            state.get_server_info(),
+            state.list_wallets(),
+            state.estimate_fees(),
+            match state.address_book.load_error() {
+                Some(err) => Task::done(Message::ContactsScreen(contacts::Message::Saved(Err(
+                    err.clone(),
+                )))),
+                None => Task::none(),
+            },
+        ]);
         (state, task)
     }
 
+    pub fn node(&self) -> Option<&Spaced> {
+        self.node.as_ref()
+    }
+
+    fn lock_timeout(&self) -> Option<time::Duration> {
+        if self.config.has_passphrase() {
+            self.config
+                .lock_timeout
+                .map(|minutes| time::Duration::from_secs(minutes * 60))
+        } else {
+            None
+        }
+    }
+
+    fn lock(&mut self) {
+        self.locked = true;
+        self.lock_screen = Default::default();
+    }
+
+    fn track<T>(&mut self, request: state::Request, result: RequestResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.connection.request_succeeded(request);
+                Some(value)
+            }
+            Err(err) => {
+                self.connection.request_failed(request, err);
+                None
+            }
+        }
+    }
+
+    fn refresh(&self) -> Task<Message> {
+        if !self.wallets_loaded {
+            return self.list_wallets();
+        }
+        let mut tasks = vec![self.get_wallet_info()];
+        match self.screen {
+            Screen::Home => {
+                tasks.push(self.get_wallet_balance());
+                tasks.push(self.get_wallet_transactions());
+            }
+            Screen::Coins => {
+                tasks.push(self.get_wallet_unspent());
+            }
+            Screen::Send => {
+                tasks.push(self.get_wallet_unspent());
+                if let Some(slabel) = self.send_screen.get_recipient_slabel() {
+                    tasks.push(self.get_space_info(slabel));
+                }
+                for slabel in self.send_screen.payment_slabels() {
+                    tasks.push(self.get_space_info(slabel));
+                }
+            }
+            Screen::Spaces => {
+                tasks.push(self.get_wallet_spaces());
+                if let Some(slabel) = self.spaces_screen.get_slabel() {
+                    tasks.push(self.get_space_info(slabel));
+                }
+            }
+            _ => {}
+        }
+        Task::batch(tasks)
+    }
+
     fn get_server_info(&self) -> Task<Message> {
         self.client.get_server_info().map(Message::ServerInfo)
     }
 
+    fn estimate_fees(&self) -> Task<Message> {
+        match self.bitcoind.as_ref() {
+            Some(bitcoind) => bitcoind.estimate_fees().map(Message::FeeEstimates),
+            None => Task::none(),
+        }
+    }
+
     fn list_wallets(&self) -> Task<Message> {
         self.client.list_wallets().map(Message::ListWallets)
     }
@@ -163,6 +322,16 @@ impl State {
         }
     }
 
+    fn get_wallet_unspent(&self) -> Task<Message> {
+        if let Some(wallet) = self.wallets.get_current() {
+            self.client
+                .get_wallet_unspent(wallet.label.to_string())
+                .map(Message::WalletUnspent)
+        } else {
+            Task::none()
+        }
+    }
+
     fn get_wallet_address(&self, address_kind: AddressKind) -> Task<Message> {
         if let Some(wallet) = self.wallets.get_current() {
             self.client
@@ -177,7 +346,25 @@ impl State {
         self.client.get_space_info(slabel).map(Message::SpaceInfo)
     }
 
+    fn confirm(
+        &mut self,
+        mut tx: PendingTx,
+        on_result: fn(Result<Option<Txid>, String>) -> Message,
+    ) -> Task<Message> {
+        // an empty fee rate goes out as the estimate the form showed for it
+        if let (
+            PendingTx::Send { fee_rate, .. } | PendingTx::Buy { fee_rate, .. },
+            Some(estimates),
+        ) = (&mut tx, self.fee_estimates)
+        {
+            fee_rate.get_or_insert(estimates.auto());
+        }
+        self.pending = Some(Pending { tx, on_result });
+        Task::none()
+    }
+
     fn navigate_to(&mut self, route: Route) -> Task<Message> {
+        self.pending = None;
         match route {
             Route::Home => {
                 if self.screen == Screen::Home {
@@ -193,7 +380,7 @@ impl State {
             }
             Route::Send => {
                 self.screen = Screen::Send;
-                self.get_wallet_spaces()
+                Task::batch([self.get_wallet_spaces(), self.get_wallet_unspent()])
             }
             Route::Receive => {
                 self.screen = Screen::Receive;
@@ -202,6 +389,10 @@ impl State {
                     self.get_wallet_address(AddressKind::Space),
                 ])
             }
+            Route::Coins => {
+                self.screen = Screen::Coins;
+                self.get_wallet_unspent()
+            }
             Route::Spaces => {
                 if self.screen == Screen::Spaces {
                     self.spaces_screen.reset();
@@ -227,6 +418,18 @@ impl State {
                 self.screen = Screen::Sign;
                 self.get_wallet_spaces()
             }
+            Route::Contacts => {
+                self.screen = Screen::Contacts;
+                Task::none()
+            }
+            Route::Composer => {
+                self.screen = Screen::Composer;
+                Task::none()
+            }
+            Route::Node => {
+                self.screen = Screen::Node;
+                Task::none()
+            }
             Route::Settings => {
                 self.screen = Screen::Settings;
                 Task::none()
@@ -236,58 +439,86 @@ impl State {
 
     pub fn update(&mut self, message: Message) -> Action {
         match message {
-            Message::Tick => {
-                let mut tasks = vec![self.get_server_info(), self.get_wallet_info()];
-                match self.screen {
-                    Screen::Home => {
-                        tasks.push(self.get_wallet_balance());
-                        tasks.push(self.get_wallet_transactions());
-                    }
-                    Screen::Spaces => {
-                        tasks.push(self.get_wallet_spaces());
-                        if let Some(slabel) = self.spaces_screen.get_slabel() {
-                            tasks.push(self.get_space_info(slabel));
-                        }
-                    }
-                    _ => {}
+            Message::Tick => Action::Task(if self.connection.is_failing() {
+                // only probe until the server answers again, the data requests would fail too
+                self.get_server_info()
+            } else {
+                Task::batch([self.get_server_info(), self.refresh(), self.estimate_fees()])
+            }),
+            Message::Activity => {
+                self.last_activity = time::Instant::now();
+                Action::Task(Task::none())
+            }
+            Message::LockCheck => {
+                if self
+                    .lock_timeout()
+                    .is_some_and(|timeout| self.last_activity.elapsed() >= timeout)
+                {
+                    self.lock();
                 }
-                Action::Task(Task::batch(tasks))
+                Action::Task(Task::none())
             }
             Message::NavigateTo(route) => Action::Task(self.navigate_to(route)),
-            Message::ServerInfo(result) => {
-                if let Ok(server_info) = result {
+            Message::ServerInfo(result) => Action::Task(match result {
+                Ok(server_info) => {
                     self.tip_height = server_info.chain.headers;
+                    if self.connection.probe_succeeded() {
+                        self.refresh()
+                    } else {
+                        Task::none()
+                    }
+                }
+                Err(err) => {
+                    self.connection.probe_failed(err);
+                    Task::none()
+                }
+            }),
+            Message::FeeEstimates(result) => {
+                // estimates are optional, the forms fall back to a plain input
+                if let Ok(fee_estimates) = result {
+                    self.fee_estimates = Some(fee_estimates);
                 }
                 Action::Task(Task::none())
             }
-            Message::ListWallets(result) => Action::Task(match result {
-                Ok(wallets_names) => {
-                    self.wallets.set_wallets(&wallets_names);
-                    if self.wallets.get_current().is_none() {
-                        if let Some(name) = self.config.wallet.as_ref() {
-                            self.wallets.set_current(name);
+            Message::ListWallets(result) => {
+                Action::Task(match self.track(state::Request::Wallets, result) {
+                    Some(wallets_names) => {
+                        self.wallets_loaded = true;
+                        self.wallets.set_wallets(&wallets_names);
+                        if self.wallets.get_current().is_none() {
+                            if let Some(name) = self.config.wallet.as_ref() {
+                                self.wallets.set_current(name);
+                            }
+                        }
+                        if let Some(wallet) = self.wallets.get_current() {
+                            self.composer_screen.set_wallet(wallet.label);
+                            self.client
+                                .load_wallet(wallet.label.clone())
+                                .map(Message::WalletLoad)
+                        } else {
+                            self.navigate_to(Route::Settings)
                         }
                     }
-                    if let Some(wallet) = self.wallets.get_current() {
-                        self.client
-                            .load_wallet(wallet.label.clone())
-                            .map(Message::WalletLoad)
-                    } else {
-                        self.navigate_to(Route::Settings)
-                    }
-                }
-                Err(_) => self.list_wallets(),
-            }),
-            Message::WalletLoad(result) => Action::Task(if result.result.is_ok() {
-                Task::batch([self.get_wallet_info(), self.navigate_to(Route::Home)])
-            } else {
-                Task::none()
-            }),
+                    None => Task::none(),
+                })
+            }
+            Message::WalletLoad(result) => Action::Task(
+                if self
+                    .track(state::Request::LoadWallet, result.result)
+                    .is_some()
+                {
+                    Task::batch([self.get_wallet_info(), self.navigate_to(Route::Home)])
+                } else {
+                    // list and load the wallets again on the next tick
+                    self.wallets_loaded = false;
+                    Task::none()
+                },
+            ),
             Message::WalletInfo(WalletResult {
                 label: wallet,
                 result,
             }) => {
-                if let Ok(wallet_info) = result {
+                if let Some(wallet_info) = self.track(state::Request::WalletInfo, result) {
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
                         wallet_state.info = Some(wallet_info);
                     }
@@ -298,9 +529,9 @@ impl State {
                 label: wallet,
                 result,
             }) => {
-                if let Ok(balance) = result {
+                if let Some(balance) = self.track(state::Request::Balance, result) {
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
-                        wallet_state.balance = balance.balance;
+                        wallet_state.balance = Some(balance);
                     }
                 }
                 Action::Task(Task::none())
@@ -309,7 +540,7 @@ impl State {
                 label: wallet,
                 result,
             }) => {
-                if let Ok(spaces) = result {
+                if let Some(spaces) = self.track(state::Request::Spaces, result) {
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
                         let mut collect = |spaces: Vec<FullSpaceOut>| -> Vec<SLabel> {
                             spaces
@@ -332,18 +563,29 @@ impl State {
                 label: wallet,
                 result,
             }) => {
-                if let Ok(transactions) = result {
+                if let Some(transactions) = self.track(state::Request::Transactions, result) {
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
                         wallet_state.transactions = transactions;
                     }
                 }
                 Action::Task(Task::none())
             }
+            Message::WalletUnspent(WalletResult {
+                label: wallet,
+                result,
+            }) => {
+                if let Some(unspent) = self.track(state::Request::Unspent, result) {
+                    if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
+                        wallet_state.unspent = unspent;
+                    }
+                }
+                Action::Task(Task::none())
+            }
             Message::WalletAddress(WalletResult {
                 label: wallet,
                 result,
             }) => {
-                if let Ok((address_kind, address)) = result {
+                if let Some((address_kind, address)) = self.track(state::Request::Address, result) {
                     if let Some(wallet_state) = self.wallets.get_data_mut(&wallet) {
                         let address = Some(state::AddressData::new(address));
                         match address_kind {
@@ -355,7 +597,7 @@ impl State {
                 Action::Task(Task::none())
             }
             Message::SpaceInfo(result) => {
-                if let Ok((slabel, out)) = result {
+                if let Some((slabel, out)) = self.track(state::Request::SpaceInfo, result) {
                     self.spaces.set(slabel, out)
                 }
                 Action::Task(Task::none())
@@ -378,35 +620,61 @@ impl State {
             }),
             Message::SendScreen(message) => Action::Task(match self.send_screen.update(message) {
                 send::Action::SendCoins {
-                    recipient,
-                    amount,
+                    payments,
                     fee_rate,
-                } => self
-                    .client
-                    .send_coins(
-                        self.wallets.get_current().unwrap().label.clone(),
-                        recipient,
-                        amount,
+                    tx_options,
+                } => self.confirm(
+                    PendingTx::Send {
+                        requests: payments
+                            .into_iter()
+                            .map(|(to, amount)| TxRequest::SendCoins { to, amount })
+                            .collect(),
                         fee_rate,
-                    )
-                    .map(|r| {
-                        Message::SendScreen(send::Message::ClientResult(r.result.map(|_| ())))
-                    }),
+                        tx_options,
+                    },
+                    |r| Message::SendScreen(send::Message::ClientResult(r.map(|_| ()))),
+                ),
                 send::Action::SendSpace {
                     recipient,
-                    slabel,
+                    slabels,
                     fee_rate,
-                } => self
-                    .client
-                    .send_space(
-                        self.wallets.get_current().unwrap().label.clone(),
-                        recipient,
-                        slabel,
+                    tx_options,
+                } => self.confirm(
+                    PendingTx::Send {
+                        requests: vec![TxRequest::Transfer {
+                            slabels,
+                            to: recipient,
+                        }],
                         fee_rate,
-                    )
-                    .map(|r| {
-                        Message::SendScreen(send::Message::ClientResult(r.result.map(|_| ())))
-                    }),
+                        tx_options,
+                    },
+                    |r| Message::SendScreen(send::Message::ClientResult(r.map(|_| ()))),
+                ),
+                send::Action::FilePick => Task::future(async move {
+                    let result = match rfd::AsyncFileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .pick_file()
+                        .await
+                    {
+                        Some(file) => tokio::fs::read_to_string(file.path())
+                            .await
+                            .map(Some)
+                            .map_err(|e| format!("Failed to read file: {}", e)),
+                        None => Ok(None),
+                    };
+                    Message::SendScreen(send::Message::CsvLoaded(result))
+                }),
+                send::Action::Queue(request) => {
+                    self.composer_screen
+                        .push(self.wallets.get_current().unwrap().label, request);
+                    Task::none()
+                }
+                send::Action::GetSpaceInfo { slabel } => self.get_space_info(slabel),
+                send::Action::GetSpacesInfo { slabels } => Task::batch(
+                    slabels
+                        .into_iter()
+                        .map(|slabel| self.get_space_info(slabel)),
+                ),
                 send::Action::ShowTransactions => self.navigate_to(Route::Home),
                 send::Action::None => Task::none(),
             }),
@@ -416,6 +684,12 @@ impl State {
                     receive::Action::None => Task::none(),
                 })
             }
+            Message::CoinsScreen(message) => {
+                Action::Task(match self.coins_screen.update(message) {
+                    coins::Action::WriteClipboard(s) => clipboard::write(s),
+                    coins::Action::ShowSpace { slabel } => self.navigate_to(Route::Space(slabel)),
+                })
+            }
             Message::SpacesScreen(message) => {
                 Action::Task(match self.spaces_screen.update(message) {
                     spaces::Action::WriteClipboard(s) => clipboard::write(s),
@@ -424,60 +698,62 @@ impl State {
                         slabel,
                         amount,
                         fee_rate,
-                    } => self
-                        .client
-                        .open_space(
-                            self.wallets.get_current().unwrap().label.clone(),
-                            slabel,
-                            amount,
+                    } => self.confirm(
+                        PendingTx::Send {
+                            requests: vec![TxRequest::Open { slabel, amount }],
                             fee_rate,
-                        )
-                        .map(|r| {
-                            Message::SpacesScreen(spaces::Message::ClientResult(
-                                r.result.map(|_| ()),
-                            ))
-                        }),
+                            tx_options: Default::default(),
+                        },
+                        |r| Message::SpacesScreen(spaces::Message::ClientResult(r.map(|_| ()))),
+                    ),
                     spaces::Action::BidSpace {
                         slabel,
                         amount,
                         fee_rate,
-                    } => self
-                        .client
-                        .bid_space(
-                            self.wallets.get_current().unwrap().label.clone(),
-                            slabel,
-                            amount,
+                    } => self.confirm(
+                        PendingTx::Send {
+                            requests: vec![TxRequest::Bid { slabel, amount }],
                             fee_rate,
-                        )
-                        .map(|r| {
-                            Message::SpacesScreen(spaces::Message::ClientResult(
-                                r.result.map(|_| ()),
-                            ))
-                        }),
-                    spaces::Action::RegisterSpace { slabel, fee_rate } => self
-                        .client
-                        .register_space(
-                            self.wallets.get_current().unwrap().label.clone(),
-                            slabel,
+                            tx_options: Default::default(),
+                        },
+                        |r| Message::SpacesScreen(spaces::Message::ClientResult(r.map(|_| ()))),
+                    ),
+                    spaces::Action::RegisterSpace { slabel, fee_rate } => self.confirm(
+                        PendingTx::Send {
+                            requests: vec![TxRequest::Register { slabel }],
                             fee_rate,
-                        )
-                        .map(|r| {
-                            Message::SpacesScreen(spaces::Message::ClientResult(
-                                r.result.map(|_| ()),
-                            ))
-                        }),
-                    spaces::Action::RenewSpace { slabel, fee_rate } => self
-                        .client
-                        .renew_space(
-                            self.wallets.get_current().unwrap().label.clone(),
-                            slabel,
+                            tx_options: Default::default(),
+                        },
+                        |r| Message::SpacesScreen(spaces::Message::ClientResult(r.map(|_| ()))),
+                    ),
+                    spaces::Action::RenewSpace { slabel, fee_rate } => self.confirm(
+                        PendingTx::Send {
+                            requests: vec![TxRequest::Renew {
+                                slabels: vec![slabel],
+                            }],
                             fee_rate,
-                        )
-                        .map(|r| {
-                            Message::SpacesScreen(spaces::Message::ClientResult(
-                                r.result.map(|_| ()),
-                            ))
-                        }),
+                            tx_options: Default::default(),
+                        },
+                        |r| Message::SpacesScreen(spaces::Message::ClientResult(r.map(|_| ()))),
+                    ),
+                    spaces::Action::RenewSpaces { slabels, fee_rate } => self.confirm(
+                        PendingTx::Send {
+                            requests: vec![TxRequest::Renew { slabels }],
+                            fee_rate,
+                            tx_options: Default::default(),
+                        },
+                        |r| Message::SpacesScreen(spaces::Message::RenewExpiringResult(r)),
+                    ),
+                    spaces::Action::ShowTransaction(txid) => {
+                        let task = self.navigate_to(Route::Home);
+                        self.home_screen.set_txid(txid);
+                        task
+                    }
+                    spaces::Action::Queue(request) => {
+                        self.composer_screen
+                            .push(self.wallets.get_current().unwrap().label, request);
+                        Task::none()
+                    }
                     spaces::Action::ShowTransactions => self.navigate_to(Route::Home),
                     spaces::Action::None => Task::none(),
                 })
@@ -485,13 +761,9 @@ impl State {
             Message::MarketScreen(message) => {
                 Action::Task(match self.market_screen.update(message) {
                     market::Action::Buy { listing, fee_rate } => self
-                        .client
-                        .buy_space(
-                            self.wallets.get_current().unwrap().label.clone(),
-                            listing,
-                            fee_rate,
-                        )
-                        .map(|r| Message::MarketScreen(market::Message::BuyResult(r.result))),
+                        .confirm(PendingTx::Buy { listing, fee_rate }, |r| {
+                            Message::MarketScreen(market::Message::BuyResult(r.map(|_| ())))
+                        }),
                     market::Action::Sell { slabel, price } => self
                         .client
                         .sell_space(
@@ -505,6 +777,90 @@ impl State {
                     market::Action::None => Task::none(),
                 })
             }
+            Message::ComposerScreen(message) => {
+                Action::Task(match self.composer_screen.update(message) {
+                    composer::Action::Send { requests, fee_rate } => self.confirm(
+                        PendingTx::Send {
+                            requests,
+                            fee_rate,
+                            tx_options: Default::default(),
+                        },
+                        |r| Message::ComposerScreen(composer::Message::ClientResult(r.map(|_| ()))),
+                    ),
+                    composer::Action::ShowTransactions => self.navigate_to(Route::Home),
+                    composer::Action::None => Task::none(),
+                })
+            }
+            Message::ConfirmScreen(message) => {
+                Action::Task(match self.confirm_screen.update(message) {
+                    confirm::Action::Cancel => {
+                        self.pending = None;
+                        Task::none()
+                    }
+                    confirm::Action::Confirm => match self.pending.take() {
+                        Some(Pending { tx, on_result }) => self
+                            .client
+                            .broadcast(self.wallets.get_current().unwrap().label.clone(), tx)
+                            .map(move |r| on_result(r.result)),
+                        None => Task::none(),
+                    },
+                })
+            }
+            Message::ContactsScreen(message) => {
+                Action::Task(match self.contacts_screen.update(message) {
+                    contacts::Action::Add(contact) => {
+                        self.address_book.add(contact);
+                        let saved = self.address_book.save();
+                        Task::done(Message::ContactsScreen(contacts::Message::Saved(saved)))
+                    }
+                    contacts::Action::Remove(index) => {
+                        self.address_book.remove(index);
+                        let saved = self.address_book.save();
+                        Task::done(Message::ContactsScreen(contacts::Message::Saved(saved)))
+                    }
+                    contacts::Action::Import(contents) => {
+                        let saved = self
+                            .address_book
+                            .import(&contents)
+                            .and_then(|_| self.address_book.save());
+                        Task::done(Message::ContactsScreen(contacts::Message::Saved(saved)))
+                    }
+                    contacts::Action::WriteClipboard(s) => clipboard::write(s),
+                    contacts::Action::FilePick => Task::future(async move {
+                        let result = match rfd::AsyncFileDialog::new()
+                            .add_filter("Contacts", &["json", "csv"])
+                            .pick_file()
+                            .await
+                        {
+                            Some(file) => tokio::fs::read_to_string(file.path())
+                                .await
+                                .map(Some)
+                                .map_err(|e| format!("Failed to read file: {}", e)),
+                            None => Ok(None),
+                        };
+                        Message::ContactsScreen(contacts::Message::FileLoaded(result))
+                    }),
+                    contacts::Action::Export(format) => {
+                        let contents = self.address_book.export(format);
+                        Task::future(async move {
+                            let file_path = rfd::AsyncFileDialog::new()
+                                .add_filter("Contacts", &[format.extension()])
+                                .set_file_name(format!("contacts.{}", format.extension()))
+                                .save_file()
+                                .await
+                                .map(|file| file.path().to_path_buf());
+                            let result = match file_path {
+                                Some(file_path) => tokio::fs::write(&file_path, contents)
+                                    .await
+                                    .map_err(|e| format!("Failed to save file: {}", e)),
+                                None => Ok(()),
+                            };
+                            Message::ContactsScreen(contacts::Message::Saved(result))
+                        })
+                    }
+                    contacts::Action::None => Task::none(),
+                })
+            }
             Message::SignScreen(message) => Action::Task(match self.sign_screen.update(message) {
                 sign::Action::FilePick => Task::future(async move {
                     let path = rfd::AsyncFileDialog::new()
@@ -562,12 +918,31 @@ impl State {
                     }),
                 sign::Action::None => Task::none(),
             }),
+            Message::NodeScreen(message) => Action::Task(match self.node_screen.update(message) {
+                node::Action::Restart => {
+                    if let Some(node) = self.node.as_ref() {
+                        node.restart();
+                    }
+                    Task::none()
+                }
+                node::Action::CopyLogs => match self.node.as_ref() {
+                    Some(node) => clipboard::write(node.logs().join("\n")),
+                    None => Task::none(),
+                },
+                node::Action::None => Task::none(),
+            }),
             Message::SettingsScreen(message) => match self.settings_screen.update(message) {
                 settings::Action::SetCurrentWallet(name) => {
                     self.wallets.set_current(&name);
+                    self.composer_screen.set_wallet(&name);
                     self.config.wallet = Some(name);
-                    self.config.save();
-                    Action::Task(self.list_wallets())
+                    let saved = self.config.save();
+                    Action::Task(Task::batch([
+                        Task::done(Message::SettingsScreen(settings::Message::ConfigSaved(
+                            saved,
+                        ))),
+                        self.list_wallets(),
+                    ]))
                 }
                 settings::Action::ExportWallet(wallet_name) => {
                     Action::Task(self.client.export_wallet(wallet_name).then(|result| {
@@ -635,9 +1010,63 @@ impl State {
                             .chain(self.list_wallets()),
                     )
                 }
-                settings::Action::ResetBackend => Action::Return(self.config.clone()),
+                settings::Action::SwitchProfile(name) => {
+                    if self.config.profile.as_ref() == Some(&name) {
+                        Action::Task(Task::none())
+                    } else {
+                        self.config.select_profile(&name);
+                        Action::Return(self.config.clone())
+                    }
+                }
+                settings::Action::AddProfile => {
+                    self.config.new_profile();
+                    Action::Return(self.config.clone())
+                }
+                settings::Action::ResetBackend => match self.config.reset() {
+                    Ok(()) => Action::Return(self.config.clone()),
+                    Err(err) => Action::Task(Task::done(Message::SettingsScreen(
+                        settings::Message::ConfigSaved(Err(err)),
+                    ))),
+                },
+                settings::Action::SetPassphrase(passphrase) => {
+                    let saved = self.config.set_passphrase(&passphrase);
+                    Action::Task(Task::done(Message::SettingsScreen(
+                        settings::Message::ConfigSaved(saved),
+                    )))
+                }
+                settings::Action::SetLockTimeout(timeout) => {
+                    self.config.lock_timeout = timeout;
+                    self.last_activity = time::Instant::now();
+                    let saved = self.config.save();
+                    Action::Task(Task::done(Message::SettingsScreen(
+                        settings::Message::ConfigSaved(saved),
+                    )))
+                }
+                settings::Action::SetHideAmounts(hide) => {
+                    self.config.hide_amounts = hide;
+                    let saved = self.config.save();
+                    Action::Task(Task::done(Message::SettingsScreen(
+                        settings::Message::ConfigSaved(saved),
+                    )))
+                }
+                settings::Action::Lock => {
+                    self.lock();
+                    Action::Task(Task::none())
+                }
                 settings::Action::None => Action::Task(Task::none()),
             },
+            Message::LockScreen(message) => match self.lock_screen.update(message) {
+                lock::Action::Unlock(passphrase) => Action::Task(
+                    Task::future(self.config.verify_passphrase(passphrase))
+                        .map(|result| Message::LockScreen(lock::Message::UnlockResult(result))),
+                ),
+                lock::Action::Unlocked => {
+                    self.locked = false;
+                    self.last_activity = time::Instant::now();
+                    Action::Task(Task::none())
+                }
+                lock::Action::None => Action::Task(Task::none()),
+            },
         }
     }
 
@@ -661,7 +1090,81 @@ impl State {
             button.on_press(Message::NavigateTo(route))
         };
 
+        let status = self.connection.status();
+        let banner_message = match status {
+            state::ConnectionStatus::Connected => self
+                .connection
+                .call_error()
+                .map(|err| format!("Spaced rejected a request: {}", err)),
+            status => {
+                let since = self
+                    .connection
+                    .last_success()
+                    .map(|instant| {
+                        format!(
+                            ", showing data from {} min ago",
+                            instant.elapsed().as_secs() / 60
+                        )
+                    })
+                    .unwrap_or_default();
+                let message = if status == state::ConnectionStatus::Offline {
+                    format!(
+                        "Spaced is unreachable{}, retrying every {}s",
+                        since,
+                        self.connection.retry_delay().as_secs()
+                    )
+                } else {
+                    format!("Connection is degraded{}", since)
+                };
+                Some(match self.connection.error() {
+                    Some(err) => format!("{}: {}", message, err),
+                    None => message,
+                })
+            }
+        };
+        let connection_banner = banner_message.map(|message| {
+            container(text(message).center().width(Fill))
+                .style(move |theme: &Theme| {
+                    let palette = theme.extended_palette();
+                    let pair = match status {
+                        state::ConnectionStatus::Offline => palette.danger.base,
+                        state::ConnectionStatus::Degraded => palette.primary.weak,
+                        state::ConnectionStatus::Connected => palette.background.strong,
+                    };
+                    container::Style::default()
+                        .background(pair.color)
+                        .color(pair.text)
+                })
+                .width(Fill)
+                .padding(5)
+        });
+
+        if self.locked {
+            // polling keeps running underneath, only the wallet data is hidden
+            return Column::new()
+                .push_maybe(connection_banner)
+                .push(self.lock_screen.view().map(Message::LockScreen))
+                .into();
+        }
+
+        if let (Some(pending), Some(_)) = (self.pending.as_ref(), self.wallets.get_current()) {
+            return Column::new()
+                .push_maybe(connection_banner)
+                .push(
+                    self.confirm_screen
+                        .view(
+                            &pending.tx,
+                            self.tip_height,
+                            &self.spaces,
+                            self.config.hide_amounts,
+                        )
+                        .map(Message::ConfirmScreen),
+                )
+                .into();
+        }
+
         Column::new()
+            .push_maybe(connection_banner)
             .push_maybe(self.wallets.get_current().and_then(|wallet| {
                 if !wallet.is_synced() {
                     Some(
@@ -687,17 +1190,31 @@ impl State {
                         Route::Receive,
                         Screen::Receive,
                     ),
+                    navbar_button("Coins", Icon::List, Route::Coins, Screen::Coins,),
                     navbar_button("Spaces", Icon::At, Route::Spaces, Screen::Spaces,),
                     navbar_button("Market", Icon::BuildingBank, Route::Market, Screen::Market,),
                     navbar_button("Sign", Icon::Signature, Route::Sign, Screen::Sign,),
-                    vertical_space(),
-                    navbar_button(
-                        "Settings",
-                        Icon::Settings,
-                        Route::Settings,
-                        Screen::Settings,
-                    ),
+                    navbar_button("Contacts", Icon::User, Route::Contacts, Screen::Contacts,),
                 ]
+                .push_maybe((!self.composer_screen.is_empty()).then(|| navbar_button(
+                    "Transaction",
+                    Icon::ListCheck,
+                    Route::Composer,
+                    Screen::Composer
+                )))
+                .push_maybe(self.node.as_ref().map(|_| navbar_button(
+                    "Node",
+                    Icon::Assembly,
+                    Route::Node,
+                    Screen::Node
+                )))
+                .push(vertical_space())
+                .push(navbar_button(
+                    "Settings",
+                    Icon::Settings,
+                    Route::Settings,
+                    Screen::Settings,
+                ))
                 .padding(10)
                 .spacing(5)
                 .width(200),
@@ -708,8 +1225,10 @@ impl State {
                             self.home_screen
                                 .view(
                                     self.tip_height,
-                                    wallet.state.balance,
+                                    wallet.state.balance.as_ref(),
+                                    self.spaces.total_bids(&wallet.state.winning_spaces),
                                     &wallet.state.transactions,
+                                    self.config.hide_amounts,
                                 )
                                 .map(Message::HomeScreen)
                         } else {
@@ -718,7 +1237,18 @@ impl State {
                     Screen::Send =>
                         if let Some(wallet) = self.wallets.get_current() {
                             self.send_screen
-                                .view(&wallet.state.owned_spaces)
+                                .view(
+                                    &self.send_screen.check_recipient(
+                                        self.network,
+                                        self.tip_height,
+                                        &self.spaces,
+                                    ),
+                                    &self.send_screen.check_payments(self.network, &self.spaces),
+                                    self.address_book.contacts_on_network(self.network),
+                                    wallet.state,
+                                    self.fee_estimates,
+                                    self.config.hide_amounts,
+                                )
                                 .map(Message::SendScreen)
                         } else {
                             center("No wallet loaded").into()
@@ -734,15 +1264,27 @@ impl State {
                         } else {
                             center("No wallet loaded").into()
                         },
+                    Screen::Coins =>
+                        if let Some(wallet) = self.wallets.get_current() {
+                            self.coins_screen
+                                .view(
+                                    self.tip_height,
+                                    &wallet.state.unspent,
+                                    self.config.hide_amounts,
+                                )
+                                .map(Message::CoinsScreen)
+                        } else {
+                            center("No wallet loaded").into()
+                        },
                     Screen::Spaces =>
                         if let Some(wallet) = self.wallets.get_current() {
                             self.spaces_screen
                                 .view(
                                     self.tip_height,
                                     &self.spaces,
-                                    &wallet.state.winning_spaces,
-                                    &wallet.state.outbid_spaces,
-                                    &wallet.state.owned_spaces,
+                                    wallet.state,
+                                    self.fee_estimates,
+                                    self.config.hide_amounts,
                                 )
                                 .map(Message::SpacesScreen)
                         } else {
@@ -751,7 +1293,11 @@ impl State {
                     Screen::Market =>
                         if let Some(wallet) = self.wallets.get_current() {
                             self.market_screen
-                                .view(wallet.state.owned_spaces.as_ref())
+                                .view(
+                                    wallet.state.owned_spaces.as_ref(),
+                                    self.fee_estimates,
+                                    self.config.hide_amounts,
+                                )
                                 .map(Message::MarketScreen)
                         } else {
                             center("No wallet loaded").into()
@@ -764,11 +1310,32 @@ impl State {
                         } else {
                             center("No wallet loaded").into()
                         },
+                    Screen::Contacts => self
+                        .contacts_screen
+                        .view(self.address_book.contacts())
+                        .map(Message::ContactsScreen),
+                    Screen::Composer =>
+                        if self.wallets.get_current().is_some() {
+                            self.composer_screen
+                                .view(self.fee_estimates, self.config.hide_amounts)
+                                .map(Message::ComposerScreen)
+                        } else {
+                            center("No wallet loaded").into()
+                        },
+                    Screen::Node =>
+                        if let Some(node) = self.node.as_ref() {
+                            self.node_screen
+                                .view(node.status(), node.logs())
+                                .map(Message::NodeScreen)
+                        } else {
+                            center("Node is not managed by Akron").into()
+                        },
                     Screen::Settings => self
                         .settings_screen
                         .view(
                             self.wallets.get_wallets(),
                             self.wallets.get_current().map(|w| w.label),
+                            &self.config,
                         )
                         .map(Message::SettingsScreen),
                 })
@@ -777,13 +1344,35 @@ impl State {
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
-        time::every(
-            if self.tip_height != 0 && self.wallets.get_current().is_some_and(|w| w.is_synced()) {
-                time::Duration::from_secs(30)
-            } else {
-                time::Duration::from_secs(5)
-            },
-        )
-        .map(|_| Message::Tick)
+        let tick = time::every(if self.connection.is_failing() {
+            self.connection.retry_delay()
+        } else if self.tip_height != 0 && self.wallets.get_current().is_some_and(|w| w.is_synced())
+        {
+            time::Duration::from_secs(30)
+        } else {
+            time::Duration::from_secs(5)
+        })
+        .map(|_| Message::Tick);
+        let mut subscriptions = vec![tick];
+        if self.screen == Screen::Node && !self.locked {
+            subscriptions.push(
+                time::every(time::Duration::from_secs(1))
+                    .map(|_| Message::NodeScreen(node::Message::Refresh)),
+            );
+        }
+        if self.lock_timeout().is_some() && !self.locked {
+            subscriptions.push(event::listen_with(|event, _, _| match event {
+                Event::Keyboard(_)
+                | Event::Mouse(mouse::Event::ButtonPressed(_))
+                | Event::Mouse(mouse::Event::WheelScrolled { .. }) => Some(Message::Activity),
+                _ => None,
+            }));
+            subscriptions
+                .push(time::every(time::Duration::from_secs(10)).map(|_| Message::LockCheck));
+        }
+        Subscription::batch(subscriptions)
     }
 }
+
+#[cfg(test)]
+mod tests;
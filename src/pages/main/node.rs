@@ -0,0 +1,88 @@
+use iced::{
+    Border, Center, Element, Fill, Theme,
+    widget::{Column, button, column, container, horizontal_space, row, scrollable, text},
+};
+
+use crate::{
+    spaced::Status,
+    widget::{
+        form::submit_button,
+        icon::{Icon, button_icon},
+        text::{text_big, text_monospace},
+    },
+};
+
+#[derive(Debug, Default)]
+pub struct State;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Refresh,
+    RestartPress,
+    CopyLogsPress,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Restart,
+    CopyLogs,
+}
+
+impl State {
+    pub fn update(&mut self, message: Message) -> Action {
+        match message {
+            Message::Refresh => Action::None,
+            Message::RestartPress => Action::Restart,
+            Message::CopyLogsPress => Action::CopyLogs,
+        }
+    }
+
+    pub fn view<'a>(&self, status: Status, logs: Vec<String>) -> Element<'a, Message> {
+        let status = match status {
+            Status::Running => "Running".to_string(),
+            Status::Restarting { attempt } => format!("Restarting (attempt {})", attempt),
+            Status::Stopped => "Stopped".to_string(),
+            Status::Failed(err) => format!("Failed: {}", err),
+        };
+        column![
+            row![
+                text_big("Node"),
+                text(status),
+                horizontal_space(),
+                button_icon(Icon::Copy)
+                    .style(button::text)
+                    .on_press(Message::CopyLogsPress),
+                submit_button("Restart", Some(Message::RestartPress)),
+            ]
+            .spacing(20)
+            .align_y(Center),
+            container(
+                scrollable(
+                    Column::from_iter(
+                        logs.into_iter()
+                            .map(|line| text_monospace(line).size(12).into())
+                    )
+                    .width(Fill)
+                    .padding(10),
+                )
+                .anchor_bottom()
+                .height(Fill),
+            )
+            .style(|theme: &Theme| {
+                let palette = theme.extended_palette();
+                container::Style::default()
+                    .background(palette.background.base.color)
+                    .border(Border {
+                        radius: 7.0.into(),
+                        width: 1.0,
+                        color: palette.background.strong.color,
+                    })
+            })
+            .height(Fill),
+        ]
+        .padding(20)
+        .spacing(20)
+        .into()
+    }
+}
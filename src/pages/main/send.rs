@@ -1,13 +1,19 @@
-use iced::Element;
-use iced::widget::column;
+use iced::{
+    Center, Element, Fill,
+    widget::{Column, Space, button, column, row, text},
+};
+
+use spaces_client::config::ExtendedNetwork;
 
+use super::state::{SpacesCollection, WalletData};
 use crate::{
     client::*,
+    contacts::Contact,
     helpers::*,
     widget::{
         form::Form,
         tabs::TabsRow,
-        text::{error_block, text_big},
+        text::{error_block, text_big, text_monospace, text_small},
     },
 };
 
@@ -16,8 +22,12 @@ pub struct State {
     asset_kind: AddressKind,
     recipient: String,
     amount: String,
-    slabel: Option<SLabel>,
+    max: bool,
+    payments: Vec<(String, Amount)>,
+    slabels: Vec<SLabel>,
     fee_rate: String,
+    confirmed_only: bool,
+    dust: String,
     error: Option<String>,
 }
 
@@ -27,8 +37,12 @@ impl Default for State {
             asset_kind: AddressKind::Coin,
             recipient: Default::default(),
             amount: Default::default(),
-            slabel: Default::default(),
+            max: false,
+            payments: Default::default(),
+            slabels: Default::default(),
             fee_rate: Default::default(),
+            confirmed_only: false,
+            dust: Default::default(),
             error: Default::default(),
         }
     }
@@ -39,26 +53,45 @@ pub enum Message {
     TabPress(AddressKind),
     RecipientInput(String),
     AmountInput(String),
-    SLabelSelect(SLabel),
+    MaxToggle(bool),
+    AddPaymentPress,
+    RemovePaymentPress(usize),
+    ImportCsvPress,
+    CsvLoaded(Result<Option<String>, String>),
+    SLabelToggle(SLabel, bool),
+    SLabelsSet(Vec<SLabel>),
     FeeRateInput(String),
+    ConfirmedOnlyToggle(bool),
+    DustInput(String),
     SendCoinsSubmit,
+    SendMaxSubmit(Amount, FeeRate),
     SendSpaceSubmit,
+    QueuePress(TxRequest),
     ClientResult(Result<(), String>),
 }
 
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
+    GetSpaceInfo {
+        slabel: SLabel,
+    },
+    GetSpacesInfo {
+        slabels: Vec<SLabel>,
+    },
     SendCoins {
-        recipient: String,
-        amount: Amount,
+        payments: Vec<(String, Amount)>,
         fee_rate: Option<FeeRate>,
+        tx_options: TxOptions,
     },
     SendSpace {
         recipient: String,
-        slabel: SLabel,
+        slabels: Vec<SLabel>,
         fee_rate: Option<FeeRate>,
+        tx_options: TxOptions,
     },
+    FilePick,
+    Queue(TxRequest),
     ShowTransactions,
 }
 
@@ -66,8 +99,155 @@ impl State {
     pub fn reset_inputs(&mut self) {
         self.recipient = Default::default();
         self.amount = Default::default();
-        self.slabel = Default::default();
+        self.max = false;
+        self.payments = Default::default();
+        self.slabels = Default::default();
         self.fee_rate = Default::default();
+        self.dust = Default::default();
+    }
+
+    pub fn get_recipient_slabel(&self) -> Option<SLabel> {
+        recipient_from_str(&self.recipient).and_then(|recipient| recipient_slabel(&recipient))
+    }
+
+    /// The `@space` handles among the listed payments.
+    pub fn payment_slabels(&self) -> Vec<SLabel> {
+        self.payments
+            .iter()
+            .filter_map(|(recipient, _)| recipient_slabel(recipient))
+            .collect()
+    }
+
+    /// Checks the listed payments the same way as the typed recipient, every `@space`
+    /// has to resolve to a registered space before the list can be sent.
+    pub fn check_payments(
+        &self,
+        network: ExtendedNetwork,
+        spaces: &SpacesCollection,
+    ) -> Result<(), String> {
+        for (recipient, _) in &self.payments {
+            if !is_recipient_on_network(recipient, network) {
+                return Err(format!("{} is not an address for {}", recipient, network));
+            }
+            let Some(slabel) = recipient_slabel(recipient) else {
+                continue;
+            };
+            match spaces.get_covenant(&slabel) {
+                None => return Err(format!("Looking up {}", slabel)),
+                Some(None) => return Err(format!("{} does not exist", slabel)),
+                Some(Some(Covenant::Transfer { .. })) => {}
+                Some(Some(_)) => return Err(format!("{} is not registered yet", slabel)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the typed recipient against the network, `@space` handles have to resolve
+    /// to a registered space. The resolved owner comes back as a note.
+    pub fn check_recipient(
+        &self,
+        network: ExtendedNetwork,
+        tip_height: u32,
+        spaces: &SpacesCollection,
+    ) -> Result<Option<String>, String> {
+        let recipient = recipient_from_str(&self.recipient)
+            .ok_or_else(|| "Not a bitcoin address or @space".to_string())?;
+        if !is_recipient_on_network(&recipient, network) {
+            return Err(format!("The address is not for {}", network));
+        }
+        let Some(slabel) = recipient_slabel(&recipient) else {
+            return Ok(None);
+        };
+        match spaces.get_covenant(&slabel) {
+            None => Err(format!("Looking up {}", slabel)),
+            Some(None) => Err(format!("{} does not exist", slabel)),
+            Some(Some(Covenant::Transfer { expire_height, .. })) => Ok(Some(format!(
+                "Owned by {}, expires {}",
+                spaces
+                    .get_script_pubkey(&slabel)
+                    .and_then(|script| script_address(script, network))
+                    .unwrap_or_else(|| "an unknown address".to_string()),
+                height_to_future_est(*expire_height, tip_height)
+            ))),
+            Some(Some(_)) => Err(format!("{} is not registered yet", slabel)),
+        }
+    }
+
+    fn recipient_note<'a>(&self, check: &Result<Option<String>, String>) -> Element<'a, Message> {
+        match check {
+            _ if self.recipient.is_empty() => Space::new(0, 0).into(),
+            Ok(Some(note)) => text_small(note.clone()).into(),
+            Ok(None) => Space::new(0, 0).into(),
+            Err(err) => text_small(err.clone()).style(text::danger).into(),
+        }
+    }
+
+    fn current_payment(&self) -> Option<(String, Amount)> {
+        Some((
+            recipient_from_str(&self.recipient)?,
+            amount_from_str(&self.amount)?,
+        ))
+    }
+
+    /// Listed payments plus the one being typed, `None` if the inputs are incomplete.
+    fn all_payments(&self) -> Option<Vec<(String, Amount)>> {
+        let mut payments = self.payments.clone();
+        if !self.recipient.is_empty() || !self.amount.is_empty() || payments.is_empty() {
+            payments.push(self.current_payment()?);
+        }
+        Some(payments)
+    }
+
+    /// Everything in the wallet's coins minus the fee for spending all of them to one
+    /// recipient. Space outputs are left out.
+    pub fn max_payment(
+        &self,
+        unspent: &[WalletOutput],
+        fee_estimates: Option<FeeEstimates>,
+    ) -> Result<(Amount, FeeRate), &'static str> {
+        let fee_rate = match fee_rate_from_str(&self.fee_rate) {
+            Some(Some(fee_rate)) => Some(fee_rate),
+            // the rate an empty field is sent with
+            Some(None) => fee_estimates.map(FeeEstimates::auto),
+            None => None,
+        }
+        .ok_or("Set a fee rate to work out the maximum amount")?;
+        let coins: Vec<&WalletOutput> = unspent
+            .iter()
+            .filter(|output| !output.is_spaceout)
+            .filter(|output| {
+                !self.confirmed_only
+                    || output
+                        .output
+                        .chain_position
+                        .confirmation_height_upper_bound()
+                        .is_some()
+            })
+            .collect();
+        let vsize = max_vsize(
+            coins
+                .iter()
+                .map(|output| output.output.txout.script_pubkey.as_script()),
+            1,
+        );
+        coins
+            .iter()
+            .fold(Amount::ZERO, |sum, output| sum + output.output.txout.value)
+            .checked_sub(fee_rate.fee_vb(vsize).unwrap_or(Amount::MAX))
+            .filter(|amount| *amount > Amount::ZERO)
+            .map(|amount| (amount, fee_rate))
+            .ok_or("Not enough coins to cover the fee")
+    }
+
+    fn tx_options(&self) -> TxOptions {
+        TxOptions {
+            confirmed_only: self.confirmed_only,
+            // the dust input is only shown on the spaces tab
+            dust: match self.asset_kind {
+                AddressKind::Space => amount_from_str(&self.dust),
+                AddressKind::Coin => None,
+            },
+        }
     }
 
     pub fn update(&mut self, message: Message) -> Action {
@@ -76,14 +256,19 @@ impl State {
             Message::TabPress(asset_kind) => {
                 self.asset_kind = asset_kind;
                 self.amount = Default::default();
-                self.slabel = Default::default();
+                self.max = false;
+                self.slabels = Default::default();
+                self.dust = Default::default();
                 Action::None
             }
             Message::RecipientInput(recipient) => {
                 if is_recipient_input(&recipient) {
                     self.recipient = recipient;
                 }
-                Action::None
+                match self.get_recipient_slabel() {
+                    Some(slabel) => Action::GetSpaceInfo { slabel },
+                    None => Action::None,
+                }
             }
             Message::AmountInput(amount) => {
                 if is_amount_input(&amount) {
@@ -91,8 +276,47 @@ impl State {
                 }
                 Action::None
             }
-            Message::SLabelSelect(slabel) => {
-                self.slabel = Some(slabel);
+            Message::MaxToggle(max) => {
+                self.max = max;
+                self.amount = Default::default();
+                Action::None
+            }
+            Message::AddPaymentPress => {
+                self.payments.push(self.current_payment().unwrap());
+                self.recipient = Default::default();
+                self.amount = Default::default();
+                Action::None
+            }
+            Message::RemovePaymentPress(index) => {
+                self.payments.remove(index);
+                Action::None
+            }
+            Message::ImportCsvPress => Action::FilePick,
+            Message::CsvLoaded(result) => {
+                match result
+                    .and_then(|contents| contents.map(|c| payments_from_csv(&c)).transpose())
+                {
+                    Ok(Some(payments)) => {
+                        self.max = false;
+                        self.payments.extend(payments);
+                        return Action::GetSpacesInfo {
+                            slabels: self.payment_slabels(),
+                        };
+                    }
+                    Ok(None) => {}
+                    Err(err) => self.error = Some(err),
+                }
+                Action::None
+            }
+            Message::SLabelToggle(slabel, checked) => {
+                self.slabels.retain(|s| s != &slabel);
+                if checked {
+                    self.slabels.push(slabel);
+                }
+                Action::None
+            }
+            Message::SLabelsSet(slabels) => {
+                self.slabels = slabels;
                 Action::None
             }
             Message::FeeRateInput(fee_rate) => {
@@ -101,22 +325,47 @@ impl State {
                 }
                 Action::None
             }
+            Message::ConfirmedOnlyToggle(confirmed_only) => {
+                self.confirmed_only = confirmed_only;
+                Action::None
+            }
+            Message::DustInput(dust) => {
+                if is_amount_input(&dust) {
+                    self.dust = dust
+                }
+                Action::None
+            }
             Message::SendCoinsSubmit => {
                 self.error = None;
                 Action::SendCoins {
-                    recipient: recipient_from_str(&self.recipient).unwrap(),
-                    amount: amount_from_str(&self.amount).unwrap(),
+                    payments: self.all_payments().unwrap(),
                     fee_rate: fee_rate_from_str(&self.fee_rate).unwrap(),
+                    tx_options: self.tx_options(),
+                }
+            }
+            Message::SendMaxSubmit(amount, fee_rate) => {
+                self.error = None;
+                Action::SendCoins {
+                    payments: vec![(recipient_from_str(&self.recipient).unwrap(), amount)],
+                    fee_rate: Some(fee_rate),
+                    tx_options: self.tx_options(),
                 }
             }
             Message::SendSpaceSubmit => {
                 self.error = None;
                 Action::SendSpace {
-                    slabel: self.slabel.clone().unwrap(),
+                    slabels: self.slabels.clone(),
                     recipient: recipient_from_str(&self.recipient).unwrap(),
                     fee_rate: fee_rate_from_str(&self.fee_rate).unwrap(),
+                    tx_options: self.tx_options(),
                 }
             }
+            Message::QueuePress(request) => {
+                self.recipient = Default::default();
+                self.amount = Default::default();
+                self.slabels = Default::default();
+                Action::Queue(request)
+            }
             Message::ClientResult(Ok(())) => {
                 self.reset_inputs();
                 Action::ShowTransactions
@@ -128,7 +377,152 @@ impl State {
         }
     }
 
-    pub fn view<'a>(&'a self, owned_spaces: &'a Vec<SLabel>) -> Element<'a, Message> {
+    fn payments_view(&self, hide_amounts: bool) -> Element<'_, Message> {
+        if self.payments.is_empty() {
+            return column![].into();
+        }
+        let total = self
+            .payments
+            .iter()
+            .fold(Amount::ZERO, |sum, (_, amount)| sum + *amount);
+        Column::from_iter(
+            self.payments
+                .iter()
+                .enumerate()
+                .map(|(index, (recipient, amount))| {
+                    row![
+                        text_monospace(recipient).width(Fill),
+                        text(format_amount(*amount, hide_amounts)),
+                        button("Remove")
+                            .style(button::text)
+                            .on_press(Message::RemovePaymentPress(index)),
+                    ]
+                    .spacing(10)
+                    .align_y(Center)
+                    .into()
+                }),
+        )
+        .push(text(format!(
+            "{} recipients, {} in total",
+            self.payments.len(),
+            format_amount(total, hide_amounts)
+        )))
+        .spacing(5)
+        .into()
+    }
+
+    fn coins_form<'a>(
+        &'a self,
+        recipient: &Result<Option<String>, String>,
+        payments: &Result<(), String>,
+        contacts: Vec<Contact>,
+        unspent: &[WalletOutput],
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
+    ) -> Element<'a, Message> {
+        let recipient_ok = recipient.is_ok();
+        let current_payment = self.current_payment().filter(|_| recipient_ok);
+        let payments_ok = payments.is_ok();
+        let max_payment = self.max_payment(unspent, fee_estimates);
+        let contact = contacts
+            .iter()
+            .find(|c| c.recipient == self.recipient)
+            .cloned();
+        let submit = if self.max {
+            max_payment
+                .ok()
+                .filter(|_| recipient_ok)
+                .map(|(amount, fee_rate)| Message::SendMaxSubmit(amount, fee_rate))
+        } else {
+            (self.all_payments().is_some()
+                && (self.recipient.is_empty() || recipient_ok)
+                && payments_ok
+                && fee_rate_from_str(&self.fee_rate).is_some())
+            .then_some(Message::SendCoinsSubmit)
+        };
+        let form = Form::new("Send", submit);
+        let form = if self.max {
+            form
+        } else {
+            form.add_text_input("Amount", "sat", &self.amount, Message::AmountInput)
+        };
+        let form = form
+            .add_text_input(
+                "To",
+                "bitcoin address or @space",
+                &self.recipient,
+                Message::RecipientInput,
+            )
+            .add_pick_list("Address book", contacts, contact, |contact: Contact| {
+                Message::RecipientInput(contact.recipient)
+            })
+            .add_buttons([
+                (
+                    "Add recipient",
+                    current_payment
+                        .is_some()
+                        .then_some(Message::AddPaymentPress),
+                ),
+                ("Import CSV", Some(Message::ImportCsvPress)),
+                (
+                    "Add to transaction",
+                    current_payment.map(|(to, amount)| {
+                        Message::QueuePress(TxRequest::SendCoins { to, amount })
+                    }),
+                ),
+            ])
+            .add_fee_rate_input(
+                &self.fee_rate,
+                fee_estimates.map(FeeEstimates::rates),
+                Message::FeeRateInput,
+            );
+        let form = if self.payments.is_empty() {
+            form.add_checkbox("Send maximum", self.max, Message::MaxToggle)
+        } else {
+            form
+        };
+        let form = form.add_checkbox(
+            "Spend only confirmed coins",
+            self.confirmed_only,
+            Message::ConfirmedOnlyToggle,
+        );
+        column![]
+            .push_maybe(
+                payments
+                    .as_ref()
+                    .err()
+                    .map(|err| text_small(err.clone()).style(text::danger)),
+            )
+            .push(self.recipient_note(recipient))
+            .push_maybe(self.max.then(|| {
+                text(match max_payment {
+                    Ok((amount, fee_rate)) => format!(
+                        "Sends {}, all coins except space outputs minus the fee at {} sat/vB",
+                        format_amount(amount, hide_amounts),
+                        fee_rate.to_sat_per_vb_ceil()
+                    ),
+                    Err(err) => err.to_string(),
+                })
+            }))
+            .push(form)
+            .spacing(10)
+            .into()
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        recipient: &Result<Option<String>, String>,
+        payments: &Result<(), String>,
+        contacts: Vec<Contact>,
+        wallet: &'a WalletData,
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
+    ) -> Element<'a, Message> {
+        let owned_spaces = &wallet.owned_spaces;
+        let contact = contacts
+            .iter()
+            .find(|c| c.recipient == self.recipient)
+            .cloned();
         column![
             TabsRow::new()
                 .add_tab(
@@ -145,54 +539,75 @@ impl State {
                 AddressKind::Coin => column![
                     text_big("Send coins"),
                     error_block(self.error.as_ref()),
-                    Form::new(
-                        "Send",
-                        (recipient_from_str(&self.recipient).is_some()
-                            && amount_from_str(&self.amount).is_some()
-                            && fee_rate_from_str(&self.fee_rate).is_some())
-                        .then_some(Message::SendCoinsSubmit),
-                    )
-                    .add_text_input("Amount", "sat", &self.amount, Message::AmountInput)
-                    .add_text_input(
-                        "To",
-                        "bitcoin address or @space",
-                        &self.recipient,
-                        Message::RecipientInput,
-                    )
-                    .add_text_input(
-                        "Fee rate",
-                        "sat/vB (auto if empty)",
-                        &self.fee_rate,
-                        Message::FeeRateInput,
-                    )
+                    self.payments_view(hide_amounts),
+                    self.coins_form(
+                        recipient,
+                        payments,
+                        contacts,
+                        &wallet.unspent,
+                        fee_estimates,
+                        hide_amounts,
+                    ),
                 ],
                 AddressKind::Space => column![
-                    text_big("Send space"),
+                    text_big("Send spaces"),
                     error_block(self.error.as_ref()),
+                    self.recipient_note(recipient),
                     Form::new(
                         "Send",
-                        (recipient_from_str(&self.recipient).is_some()
-                            && self.slabel.is_some()
+                        (recipient.is_ok()
+                            && !self.slabels.is_empty()
                             && fee_rate_from_str(&self.fee_rate).is_some())
                         .then_some(Message::SendSpaceSubmit),
                     )
-                    .add_pick_list(
-                        "Space",
+                    .add_checklist(
+                        "Spaces",
                         owned_spaces.as_slice(),
-                        self.slabel.as_ref(),
-                        Message::SLabelSelect
+                        &self.slabels,
+                        Message::SLabelToggle
                     )
+                    .add_buttons([
+                        (
+                            "Select all",
+                            Some(Message::SLabelsSet(owned_spaces.clone()))
+                        ),
+                        ("Clear", Some(Message::SLabelsSet(Vec::new()))),
+                    ])
                     .add_text_input(
                         "To",
                         "bitcoin address or @space",
                         &self.recipient,
                         Message::RecipientInput,
                     )
-                    .add_text_input(
-                        "Fee rate",
-                        "sat/vB (auto if empty)",
+                    .add_pick_list("Address book", contacts, contact, |contact: Contact| {
+                        Message::RecipientInput(contact.recipient)
+                    })
+                    .add_buttons([(
+                        "Add to transaction",
+                        recipient_from_str(&self.recipient)
+                            .filter(|_| recipient.is_ok() && !self.slabels.is_empty())
+                            .map(|to| {
+                                Message::QueuePress(TxRequest::Transfer {
+                                    slabels: self.slabels.clone(),
+                                    to,
+                                })
+                            })
+                    )])
+                    .add_fee_rate_input(
                         &self.fee_rate,
-                        Message::FeeRateInput,
+                        fee_estimates.map(FeeEstimates::rates),
+                        Message::FeeRateInput
+                    )
+                    .add_text_input(
+                        "Space output value",
+                        "sat (auto if empty)",
+                        &self.dust,
+                        Message::DustInput,
+                    )
+                    .add_checkbox(
+                        "Spend only confirmed coins",
+                        self.confirmed_only,
+                        Message::ConfirmedOnlyToggle,
                     ),
                 ],
             }
@@ -1,16 +1,42 @@
 use iced::{
     Center, Element, Fill, Shrink,
-    widget::{button, column, row, text},
+    widget::{button, checkbox, column, row, text},
 };
 
-use crate::widget::{
-    form::{pick_list, submit_button, text_input},
-    text::{error_block, text_big},
+use crate::{
+    Config,
+    widget::{
+        form::{pick_list, submit_button, text_input},
+        text::{error_block, text_big, text_small},
+    },
 };
 
+/// Auto-lock choices, in minutes.
+const LOCK_TIMEOUTS: [LockTimeout; 6] = [
+    LockTimeout(None),
+    LockTimeout(Some(1)),
+    LockTimeout(Some(5)),
+    LockTimeout(Some(15)),
+    LockTimeout(Some(30)),
+    LockTimeout(Some(60)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockTimeout(Option<u64>);
+
+impl std::fmt::Display for LockTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            None => write!(f, "Never"),
+            Some(minutes) => write!(f, "After {} min", minutes),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct State {
     new_wallet_name: String,
+    passphrase: String,
     error: Option<String>,
 }
 
@@ -21,8 +47,16 @@ pub enum Message {
     NewWalletInput(String),
     CreateWalletPress,
     ImportWalletPress,
+    ProfileSelect(String),
+    AddProfilePress,
     ResetBackendPress,
+    PassphraseInput(String),
+    SetPassphrasePress,
+    LockTimeoutSelect(LockTimeout),
+    LockPress,
+    HideAmountsToggle(bool),
     WalletFileSaved(Result<(), String>),
+    ConfigSaved(Result<(), String>),
     WalletCreated(Result<(), String>),
     WalletFileLoaded(Option<String>),
     WalletFileImported(Result<(), String>),
@@ -36,7 +70,13 @@ pub enum Action {
     CreateWallet(String),
     FilePick,
     ImportWallet(String),
+    SwitchProfile(String),
+    AddProfile,
     ResetBackend,
+    SetPassphrase(String),
+    SetLockTimeout(Option<u64>),
+    Lock,
+    SetHideAmounts(bool),
 }
 
 impl State {
@@ -53,8 +93,22 @@ impl State {
             }
             Message::CreateWalletPress => Action::CreateWallet(self.new_wallet_name.to_string()),
             Message::ImportWalletPress => Action::FilePick,
+            Message::ProfileSelect(p) => Action::SwitchProfile(p),
+            Message::AddProfilePress => Action::AddProfile,
             Message::ResetBackendPress => Action::ResetBackend,
-            Message::WalletFileSaved(result) | Message::WalletFileImported(result) => {
+            Message::PassphraseInput(p) => {
+                self.passphrase = p;
+                Action::None
+            }
+            Message::SetPassphrasePress => {
+                Action::SetPassphrase(std::mem::take(&mut self.passphrase))
+            }
+            Message::LockTimeoutSelect(timeout) => Action::SetLockTimeout(timeout.0),
+            Message::LockPress => Action::Lock,
+            Message::HideAmountsToggle(hide) => Action::SetHideAmounts(hide),
+            Message::WalletFileSaved(result)
+            | Message::WalletFileImported(result)
+            | Message::ConfigSaved(result) => {
                 if let Err(err) = result {
                     self.error = Some(err);
                 }
@@ -82,7 +136,9 @@ impl State {
         &'a self,
         wallets_names: Vec<&'a String>,
         wallet_name: Option<&'a String>,
+        config: &'a Config,
     ) -> Element<'a, Message> {
+        let has_passphrase = config.has_passphrase();
         column![
             column![
                 text_big("Wallet"),
@@ -116,13 +172,72 @@ impl State {
             .spacing(10),
             column![
                 text_big("Backend"),
-                button(text("Reset backend settings").align_x(Center).width(Fill))
+                row![
+                    pick_list(config.profiles(), config.profile.as_ref(), |p| {
+                        Message::ProfileSelect(p.to_string())
+                    })
+                    .width(Fill),
+                    submit_button("Add", Some(Message::AddProfilePress)),
+                ]
+                .spacing(20),
+                button(text("Remove backend profile").align_x(Center).width(Fill))
                     .on_press(Message::ResetBackendPress)
                     .style(button::danger)
                     .padding(10)
                     .width(Fill),
             ]
-            .spacing(10)
+            .spacing(10),
+            column![
+                text_big("Security"),
+                row![
+                    text_input(
+                        if has_passphrase {
+                            "new passphrase, empty to remove"
+                        } else {
+                            "master passphrase"
+                        },
+                        &self.passphrase
+                    )
+                    .secure(true)
+                    .on_input(Message::PassphraseInput),
+                    submit_button(
+                        if has_passphrase { "Change" } else { "Set" },
+                        if has_passphrase || !self.passphrase.is_empty() {
+                            Some(Message::SetPassphrasePress)
+                        } else {
+                            None
+                        }
+                    ),
+                ]
+                .spacing(20),
+            ]
+            .push(if has_passphrase {
+                column![
+                    text_small("Auto-lock, the master passphrase unlocks the wallet"),
+                    row![
+                        pick_list(
+                            LOCK_TIMEOUTS,
+                            Some(LockTimeout(config.lock_timeout)),
+                            Message::LockTimeoutSelect
+                        )
+                        .width(Fill),
+                        submit_button("Lock now", Some(Message::LockPress)),
+                    ]
+                    .spacing(20),
+                ]
+                .spacing(5)
+            } else {
+                column![text_small(
+                    "Set a master passphrase to lock the wallet and to auto-lock it when idle"
+                )]
+            })
+            .spacing(10),
+            column![
+                text_big("Privacy"),
+                checkbox("Hide balances and amounts", config.hide_amounts)
+                    .on_toggle(Message::HideAmountsToggle),
+            ]
+            .spacing(10),
         ]
         .padding([60, 100])
         .spacing(20)
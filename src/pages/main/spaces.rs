@@ -6,7 +6,7 @@ use iced::{
     },
 };
 
-use super::state::SpacesCollection;
+use super::state::{SpacesCollection, WalletData};
 use crate::{
     client::*,
     helpers::*,
@@ -33,9 +33,12 @@ pub struct State {
     filter: Filter,
     amount: String,
     fee_rate: String,
+    renew_window: String,
     error: Option<String>,
 }
 
+const DEFAULT_RENEW_WINDOW_DAYS: u32 = 30;
+
 #[derive(Debug, Clone)]
 pub enum Message {
     BackPress,
@@ -50,6 +53,10 @@ pub enum Message {
     BidSubmit,
     RegisterSubmit,
     RenewSubmit,
+    QueuePress(TxRequest),
+    RenewWindowInput(String),
+    RenewExpiringPress(Vec<SLabel>),
+    RenewExpiringResult(Result<Option<Txid>, String>),
     ClientResult(Result<(), String>),
 }
 
@@ -78,6 +85,12 @@ pub enum Action {
         slabel: SLabel,
         fee_rate: Option<FeeRate>,
     },
+    RenewSpaces {
+        slabels: Vec<SLabel>,
+        fee_rate: Option<FeeRate>,
+    },
+    Queue(TxRequest),
+    ShowTransaction(Txid),
     ShowTransactions,
 }
 
@@ -105,6 +118,14 @@ impl State {
         self.slabel.clone()
     }
 
+    fn renew_window(&self) -> Option<u32> {
+        if self.renew_window.is_empty() {
+            Some(DEFAULT_RENEW_WINDOW_DAYS)
+        } else {
+            self.renew_window.parse().ok()
+        }
+    }
+
     pub fn update(&mut self, message: Message) -> Action {
         self.error = None;
         match message {
@@ -164,6 +185,32 @@ impl State {
                 slabel: self.slabel.as_ref().unwrap().clone(),
                 fee_rate: fee_rate_from_str(&self.fee_rate).unwrap(),
             },
+            Message::QueuePress(request) => {
+                self.reset_inputs();
+                Action::Queue(request)
+            }
+            Message::RenewWindowInput(days) => {
+                if is_amount_input(&days) {
+                    self.renew_window = days;
+                }
+                Action::None
+            }
+            Message::RenewExpiringPress(slabels) => Action::RenewSpaces {
+                slabels,
+                fee_rate: fee_rate_from_str(&self.fee_rate).unwrap(),
+            },
+            Message::RenewExpiringResult(Ok(Some(txid))) => {
+                self.reset_inputs();
+                Action::ShowTransaction(txid)
+            }
+            Message::RenewExpiringResult(Ok(None)) => {
+                self.reset_inputs();
+                Action::ShowTransactions
+            }
+            Message::RenewExpiringResult(Err(err)) => {
+                self.error = Some(err);
+                Action::None
+            }
             Message::ClientResult(Ok(())) => {
                 self.reset_inputs();
                 Action::ShowTransactions
@@ -175,7 +222,7 @@ impl State {
         }
     }
 
-    fn open_form(&self) -> Element<'_, Message> {
+    fn open_form(&self, fee_estimates: Option<FeeEstimates>) -> Element<'_, Message> {
         Form::new(
             "Open",
             (amount_from_str(&self.amount).is_some()
@@ -183,16 +230,28 @@ impl State {
             .then_some(Message::OpenSubmit),
         )
         .add_text_input("Amount", "sat", &self.amount, Message::AmountInput)
-        .add_text_input(
-            "Fee rate",
-            "sat/vB (auto if empty)",
+        .add_buttons([(
+            "Add to transaction",
+            amount_from_str(&self.amount).map(|amount| {
+                Message::QueuePress(TxRequest::Open {
+                    slabel: self.slabel.clone().unwrap(),
+                    amount,
+                })
+            }),
+        )])
+        .add_fee_rate_input(
             &self.fee_rate,
+            fee_estimates.map(FeeEstimates::rates),
             Message::FeeRateInput,
         )
         .into()
     }
 
-    fn bid_form(&self, current_bid: Amount) -> Element<'_, Message> {
+    fn bid_form(
+        &self,
+        current_bid: Amount,
+        fee_estimates: Option<FeeEstimates>,
+    ) -> Element<'_, Message> {
         Form::new(
             "Bid",
             (amount_from_str(&self.amount).is_some_and(|amount| amount > current_bid)
@@ -200,50 +259,70 @@ impl State {
             .then_some(Message::BidSubmit),
         )
         .add_text_input("Amount", "sat", &self.amount, Message::AmountInput)
-        .add_text_input(
-            "Fee rate",
-            "sat/vB (auto if empty)",
+        .add_buttons([(
+            "Add to transaction",
+            amount_from_str(&self.amount)
+                .filter(|amount| *amount > current_bid)
+                .map(|amount| {
+                    Message::QueuePress(TxRequest::Bid {
+                        slabel: self.slabel.clone().unwrap(),
+                        amount,
+                    })
+                }),
+        )])
+        .add_fee_rate_input(
             &self.fee_rate,
+            fee_estimates.map(FeeEstimates::rates),
             Message::FeeRateInput,
         )
         .into()
     }
 
-    fn register_form(&self) -> Element<'_, Message> {
+    fn register_form(&self, fee_estimates: Option<FeeEstimates>) -> Element<'_, Message> {
         Form::new(
             "Register",
             fee_rate_from_str(&self.fee_rate).map(|_| Message::RegisterSubmit),
         )
-        .add_text_input(
-            "Fee rate",
-            "sat/vB (auto if empty)",
+        .add_buttons([(
+            "Add to transaction",
+            Some(Message::QueuePress(TxRequest::Register {
+                slabel: self.slabel.clone().unwrap(),
+            })),
+        )])
+        .add_fee_rate_input(
             &self.fee_rate,
+            fee_estimates.map(FeeEstimates::rates),
             Message::FeeRateInput,
         )
         .into()
     }
 
-    fn renew_form(&self) -> Element<'_, Message> {
+    fn renew_form(&self, fee_estimates: Option<FeeEstimates>) -> Element<'_, Message> {
         Form::new(
             "Renew",
             fee_rate_from_str(&self.fee_rate).map(|_| Message::RenewSubmit),
         )
-        .add_text_input(
-            "Fee rate",
-            "sat/vB (auto if empty)",
+        .add_buttons([(
+            "Add to transaction",
+            Some(Message::QueuePress(TxRequest::Renew {
+                slabels: vec![self.slabel.clone().unwrap()],
+            })),
+        )])
+        .add_fee_rate_input(
             &self.fee_rate,
+            fee_estimates.map(FeeEstimates::rates),
             Message::FeeRateInput,
         )
         .into()
     }
 
-    fn open_view(&self) -> Element<'_, Message> {
+    fn open_view(&self, fee_estimates: Option<FeeEstimates>) -> Element<'_, Message> {
         row![
             timeline::view(0, "Make an open to propose the space for auction"),
             column![
                 text_big("Open space"),
                 error_block(self.error.as_ref()),
-                self.open_form(),
+                self.open_form(fee_estimates),
             ]
             .spacing(10),
         ]
@@ -256,6 +335,8 @@ impl State {
         claim_height: Option<u32>,
         current_bid: Amount,
         is_winning: bool,
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
     ) -> Element<'_, Message> {
         row![
             timeline::view(
@@ -270,7 +351,7 @@ impl State {
                 error_block(self.error.as_ref()),
                 row![
                     text("Current bid").size(14),
-                    text_bold(format_amount(current_bid).to_string()).size(14),
+                    text_bold(format_amount(current_bid, hide_amounts)).size(14),
                 ]
                 .spacing(5),
                 row![
@@ -278,14 +359,20 @@ impl State {
                     text_bold(if is_winning { "you" } else { "not you" }).size(14),
                 ]
                 .spacing(5),
-                self.bid_form(current_bid),
+                self.bid_form(current_bid, fee_estimates),
             ]
             .spacing(10),
         ]
         .into()
     }
 
-    fn register_view(&self, current_bid: Amount, is_winning: bool) -> Element<'_, Message> {
+    fn register_view(
+        &self,
+        current_bid: Amount,
+        is_winning: bool,
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
+    ) -> Element<'_, Message> {
         row![
             timeline::view(
                 3,
@@ -299,7 +386,7 @@ impl State {
                 column![
                     text_big("Register space"),
                     error_block(self.error.as_ref()),
-                    self.register_form(),
+                    self.register_form(fee_estimates),
                 ]
                 .spacing(10)
             } else {
@@ -308,10 +395,10 @@ impl State {
                     error_block(self.error.as_ref()),
                     row![
                         text("Current bid").size(14),
-                        text_bold(format_amount(current_bid)).size(14),
+                        text_bold(format_amount(current_bid, hide_amounts)).size(14),
                     ]
                     .spacing(5),
-                    self.bid_form(current_bid),
+                    self.bid_form(current_bid, fee_estimates),
                 ]
                 .spacing(10)
             }
@@ -325,6 +412,7 @@ impl State {
         expire_height: u32,
         outpoint: &'a OutPoint,
         is_owned: bool,
+        fee_estimates: Option<FeeEstimates>,
     ) -> Element<'a, Message> {
         row![
             column![
@@ -356,7 +444,7 @@ impl State {
                 column![
                     text_big("Renew space"),
                     error_block(self.error.as_ref()),
-                    self.renew_form(),
+                    self.renew_form(fee_estimates),
                 ]
                 .spacing(10)
             } else {
@@ -371,10 +459,13 @@ impl State {
         &'a self,
         tip_height: u32,
         spaces: &'a SpacesCollection,
-        winning_spaces: &'a [SLabel],
-        outbid_spaces: &'a [SLabel],
-        owned_spaces: &'a [SLabel],
+        wallet: &'a WalletData,
+        fee_estimates: Option<FeeEstimates>,
+        hide_amounts: bool,
     ) -> Element<'a, Message> {
+        let winning_spaces = &wallet.winning_spaces;
+        let outbid_spaces = &wallet.outbid_spaces;
+        let owned_spaces = &wallet.owned_spaces;
         if let Some(slabel) = self.slabel.as_ref() {
             let covenant = spaces.get_covenant(slabel);
             column![
@@ -392,7 +483,7 @@ impl State {
                 horizontal_rule(3),
                 match covenant {
                     None => center(text("Loading")).into(),
-                    Some(None) => self.open_view(),
+                    Some(None) => self.open_view(fee_estimates),
                     Some(Some(Covenant::Bid {
                         claim_height,
                         total_burned,
@@ -400,9 +491,21 @@ impl State {
                     })) => {
                         let is_winning = winning_spaces.contains(slabel);
                         if claim_height.is_some_and(|height| height <= tip_height) {
-                            self.register_view(*total_burned, is_winning)
+                            self.register_view(
+                                *total_burned,
+                                is_winning,
+                                fee_estimates,
+                                hide_amounts,
+                            )
                         } else {
-                            self.bid_view(tip_height, *claim_height, *total_burned, is_winning)
+                            self.bid_view(
+                                tip_height,
+                                *claim_height,
+                                *total_burned,
+                                is_winning,
+                                fee_estimates,
+                                hide_amounts,
+                            )
                         }
                     }
                     Some(Some(Covenant::Transfer { expire_height, .. })) => {
@@ -412,6 +515,7 @@ impl State {
                             *expire_height,
                             spaces.get_outpoint(slabel).unwrap(),
                             is_owned,
+                            fee_estimates,
                         )
                     }
                     Some(Some(Covenant::Reserved)) => center(text("The space is locked")).into(),
@@ -458,7 +562,7 @@ impl State {
                                 text_small("In auction"),
                                 text_small(format!(
                                     "Highest bid: {} ({})",
-                                    format_amount(*total_burned),
+                                    format_amount(*total_burned, hide_amounts),
                                     if is_winning { "you" } else { "not you" }
                                 )),
                                 if is_claimable {
@@ -573,17 +677,54 @@ impl State {
                     )
                     .push_maybe(if self.search.is_empty() {
                         Some(
-                            TabsRow::new()
-                                .add_tab(
-                                    "Owned",
-                                    self.filter == Filter::Owned,
-                                    Message::FilterPress(Filter::Owned),
-                                )
-                                .add_tab(
-                                    "Bidding",
-                                    self.filter == Filter::Bidding,
-                                    Message::FilterPress(Filter::Bidding),
-                                ),
+                            column![
+                                TabsRow::new()
+                                    .add_tab(
+                                        "Owned",
+                                        self.filter == Filter::Owned,
+                                        Message::FilterPress(Filter::Owned),
+                                    )
+                                    .add_tab(
+                                        "Bidding",
+                                        self.filter == Filter::Bidding,
+                                        Message::FilterPress(Filter::Bidding),
+                                    )
+                            ]
+                            .push_maybe((self.filter == Filter::Owned).then(|| {
+                                let expiring = self.renew_window().map_or_else(Vec::new, |days| {
+                                    spaces.expiring(
+                                        owned_spaces,
+                                        tip_height.saturating_add(days.saturating_mul(144)),
+                                    )
+                                });
+                                column![
+                                    row![
+                                        text("Spaces expiring within"),
+                                        text_input("30", &self.renew_window)
+                                            .on_input(Message::RenewWindowInput)
+                                            .width(80),
+                                        text("days:"),
+                                        text_bold(expiring.len().to_string()),
+                                    ]
+                                    .spacing(10)
+                                    .align_y(Center)
+                                ]
+                                .push_maybe((!expiring.is_empty()).then(|| {
+                                    Form::new(
+                                        "Renew all",
+                                        fee_rate_from_str(&self.fee_rate)
+                                            .map(|_| Message::RenewExpiringPress(expiring)),
+                                    )
+                                    .add_fee_rate_input(
+                                        &self.fee_rate,
+                                        fee_estimates.map(FeeEstimates::rates),
+                                        Message::FeeRateInput,
+                                    )
+                                }))
+                                .spacing(10)
+                            }))
+                            .push(error_block(self.error.as_ref()))
+                            .spacing(10),
                         )
                     } else {
                         None
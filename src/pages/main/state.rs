@@ -1,12 +1,22 @@
 use iced::widget::qr_code::Data as QrCode;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 
 use spaces_client::wallets::{TxInfo, WalletInfoWithProgress, WalletProgressUpdate};
 use spaces_protocol::{Covenant, FullSpaceOut, slabel::SLabel};
-use spaces_wallet::bitcoin::{Amount, OutPoint};
+use spaces_wallet::{
+    Balance, WalletOutput,
+    bitcoin::{Amount, OutPoint, ScriptBuf},
+};
+
+use crate::client::RequestError;
 
 #[derive(Debug)]
 pub struct SpaceData {
     outpoint: OutPoint,
+    script_pubkey: ScriptBuf,
     covenant: Covenant,
 }
 #[derive(Debug, Default)]
@@ -17,6 +27,7 @@ impl SpacesCollection {
             slabel,
             out.map(|out| SpaceData {
                 outpoint: out.outpoint(),
+                script_pubkey: out.spaceout.script_pubkey,
                 covenant: out.spaceout.space.unwrap().covenant,
             }),
         );
@@ -28,9 +39,40 @@ impl SpacesCollection {
             .and_then(|o| o.as_ref().map(|s| &s.outpoint))
     }
 
+    pub fn get_script_pubkey(&self, slabel: &SLabel) -> Option<&ScriptBuf> {
+        self.0
+            .get(slabel)
+            .and_then(|o| o.as_ref().map(|s| &s.script_pubkey))
+    }
+
     pub fn get_covenant(&self, slabel: &SLabel) -> Option<Option<&Covenant>> {
         self.0.get(slabel).map(|o| o.as_ref().map(|s| &s.covenant))
     }
+
+    /// Registered spaces among the given ones that expire at or before the height.
+    pub fn expiring(&self, slabels: &[SLabel], height: u32) -> Vec<SLabel> {
+        slabels
+            .iter()
+            .filter(|slabel| {
+                matches!(
+                    self.get_covenant(slabel),
+                    Some(Some(Covenant::Transfer { expire_height, .. })) if *expire_height <= height
+                )
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Sum of the current bids on the given spaces, as far as they are loaded.
+    pub fn total_bids(&self, slabels: &[SLabel]) -> Amount {
+        slabels
+            .iter()
+            .filter_map(|slabel| match self.get_covenant(slabel) {
+                Some(Some(Covenant::Bid { total_burned, .. })) => Some(*total_burned),
+                _ => None,
+            })
+            .fold(Amount::ZERO, |sum, amount| sum + amount)
+    }
 }
 
 #[derive(Debug)]
@@ -56,13 +98,14 @@ impl AddressData {
 #[derive(Debug, Default)]
 pub struct WalletData {
     pub info: Option<WalletInfoWithProgress>,
-    pub balance: Amount,
+    pub balance: Option<Balance>,
     pub coin_address: Option<AddressData>,
     pub space_address: Option<AddressData>,
     pub winning_spaces: Vec<SLabel>,
     pub outbid_spaces: Vec<SLabel>,
     pub owned_spaces: Vec<SLabel>,
     pub transactions: Vec<TxInfo>,
+    pub unspent: Vec<WalletOutput>,
 }
 pub struct WalletEntry<'a> {
     pub label: &'a String,
@@ -173,3 +216,104 @@ impl WalletsCollection {
         self.wallets.get_mut(label).and_then(|state| state.as_mut())
     }
 }
+
+const OFFLINE_AFTER_FAILURES: u32 = 3;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Connected,
+    Degraded,
+    Offline,
+}
+
+/// Data requests whose failures are tracked apart, so one succeeding doesn't hide another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Request {
+    Wallets,
+    LoadWallet,
+    WalletInfo,
+    Balance,
+    Spaces,
+    Transactions,
+    Unspent,
+    Address,
+    SpaceInfo,
+}
+
+/// Health of the spaced connection: `failures` counts failed server info probes in a row,
+/// `request_errors` holds the last failure of each data request kind. Only transport
+/// errors count against the connection, calls spaced rejected are just reported.
+#[derive(Debug, Default)]
+pub struct Connection {
+    failures: u32,
+    error: Option<String>,
+    request_errors: BTreeMap<Request, RequestError>,
+    last_success: Option<Instant>,
+}
+
+impl Connection {
+    pub fn status(&self) -> ConnectionStatus {
+        if self.failures >= OFFLINE_AFTER_FAILURES {
+            ConnectionStatus::Offline
+        } else if self.failures > 0 || self.transport_error().is_some() {
+            ConnectionStatus::Degraded
+        } else {
+            ConnectionStatus::Connected
+        }
+    }
+
+    /// Returns `true` when the probe succeeds after previous failures.
+    pub fn probe_succeeded(&mut self) -> bool {
+        let recovered = self.failures > 0;
+        self.failures = 0;
+        self.error = None;
+        self.last_success = Some(Instant::now());
+        recovered
+    }
+
+    pub fn probe_failed(&mut self, error: String) {
+        self.failures += 1;
+        self.error = Some(error);
+    }
+
+    pub fn request_succeeded(&mut self, request: Request) {
+        self.request_errors.remove(&request);
+        self.last_success = Some(Instant::now());
+    }
+
+    pub fn request_failed(&mut self, request: Request, error: RequestError) {
+        self.request_errors.insert(request, error);
+    }
+
+    fn transport_error(&self) -> Option<&String> {
+        self.request_errors.values().find_map(|error| match error {
+            RequestError::Transport(error) => Some(error),
+            RequestError::Call(_) => None,
+        })
+    }
+
+    /// An error spaced answered a data request with, while the connection itself is fine.
+    pub fn call_error(&self) -> Option<&String> {
+        self.request_errors.values().find_map(|error| match error {
+            RequestError::Call(error) => Some(error),
+            RequestError::Transport(_) => None,
+        })
+    }
+
+    pub fn is_failing(&self) -> bool {
+        self.failures > 0
+    }
+
+    pub fn retry_delay(&self) -> Duration {
+        Duration::from_secs(5 << self.failures.saturating_sub(1).min(5)).min(MAX_RETRY_DELAY)
+    }
+
+    pub fn error(&self) -> Option<&String> {
+        self.error.as_ref().or(self.transport_error())
+    }
+
+    pub fn last_success(&self) -> Option<Instant> {
+        self.last_success
+    }
+}
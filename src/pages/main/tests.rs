@@ -0,0 +1,606 @@
+use iced::{Task, widget::text_editor};
+use serde_json::json;
+use std::{collections::VecDeque, sync::Arc};
+
+use super::{
+    Action, Message, Route, Screen, State, composer, confirm, lock, market, send, settings, sign,
+    spaces, state::ConnectionStatus,
+};
+use crate::{
+    client::{
+        AddressKind, Amount, Client, ClientOptions, FeeEstimates, FeeRate, NostrEvent,
+        RequestError, SLabel, TxRequest,
+    },
+    mock::{self, MockSpaced},
+};
+
+async fn drive(state: &mut State, task: Task<Message>) {
+    let mut queue: VecDeque<Message> = mock::outputs(task).await.into();
+    while let Some(message) = queue.pop_front() {
+        match state.update(message) {
+            Action::Return(_) => panic!("unexpected return to setup"),
+            Action::Task(task) => queue.extend(mock::outputs(task).await),
+        }
+    }
+}
+
+async fn dispatch(state: &mut State, message: Message) {
+    drive(state, Task::done(message)).await
+}
+
+async fn start(spaced: &MockSpaced) -> State {
+    let mut config = spaced.config();
+    config.wallet = Some(mock::WALLET.to_string());
+    let client = Client::new(spaced.url(), ClientOptions::default()).unwrap();
+    let (mut state, task) = State::run(config, client, None);
+    drive(&mut state, task).await;
+    state
+}
+
+async fn confirm(state: &mut State) {
+    dispatch(
+        state,
+        Message::ConfirmScreen(confirm::Message::ConfirmPress),
+    )
+    .await
+}
+
+fn slabel() -> SLabel {
+    SLabel::from_str_unprefixed("example").unwrap()
+}
+
+#[tokio::test]
+async fn loads_configured_wallet() {
+    let spaced = MockSpaced::start().await;
+    let state = start(&spaced).await;
+
+    assert_eq!(spaced.calls("walletload"), vec![json!([mock::WALLET])]);
+    assert_eq!(state.wallets.get_current().unwrap().label, mock::WALLET);
+    assert_eq!(state.screen, Screen::Home);
+    assert_eq!(state.tip_height, 100);
+    assert!(!spaced.calls("walletgetbalance").is_empty());
+    let balance = state.wallets.get_current().unwrap().state.balance.as_ref();
+    assert_eq!(balance.unwrap().details.balance.confirmed.to_sat(), 100000);
+}
+
+#[tokio::test]
+async fn sends_coins() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::AmountInput("1000".to_string()),
+        send::Message::FeeRateInput("2".to_string()),
+        send::Message::SendCoinsSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    // nothing is broadcast until confirmed
+    assert!(state.pending.is_some());
+    assert!(spaced.calls("walletsendrequest").is_empty());
+    dispatch(
+        &mut state,
+        Message::ConfirmScreen(confirm::Message::CancelPress),
+    )
+    .await;
+    assert!(state.pending.is_none());
+    assert!(spaced.calls("walletsendrequest").is_empty());
+
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::SendCoinsSubmit),
+    )
+    .await;
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][0], json!(mock::WALLET));
+    assert!(calls[0][1].to_string().contains(mock::ADDRESS));
+    assert_eq!(calls[0][1]["confirmed_only"], json!(false));
+    assert_eq!(state.screen, Screen::Home);
+}
+
+#[tokio::test]
+async fn sends_the_estimated_fee_rate_when_left_empty() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    let estimates = FeeEstimates {
+        fast: 13,
+        normal: 5,
+        slow: 1,
+    };
+    dispatch(&mut state, Message::FeeEstimates(Ok(estimates))).await;
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::AmountInput("1000".to_string()),
+        send::Message::SendCoinsSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][1]["fee_rate"], json!(estimates.auto()));
+    assert_eq!(estimates.auto(), FeeRate::from_sat_per_vb(5).unwrap());
+}
+
+#[tokio::test]
+async fn resolves_space_recipient() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::RecipientInput("@example".to_string())),
+    )
+    .await;
+
+    assert_eq!(spaced.calls("getspace").len(), 1);
+    assert_eq!(state.send_screen.get_recipient_slabel(), Some(slabel()));
+    // the mock knows no spaces
+    assert!(matches!(state.spaces.get_covenant(&slabel()), Some(None)));
+}
+
+#[tokio::test]
+async fn sends_confirmed_coins_only() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Coins)).await;
+    assert_eq!(
+        spaced.calls("walletlistunspent"),
+        vec![json!([mock::WALLET])]
+    );
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::AmountInput("1000".to_string()),
+        send::Message::ConfirmedOnlyToggle(true),
+        send::Message::SendCoinsSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls[0][1]["confirmed_only"], json!(true));
+}
+
+#[tokio::test]
+async fn stays_on_send_screen_when_rejected() {
+    let spaced = MockSpaced::start().await;
+    spaced.respond("walletsendrequest", Err("insufficient funds".to_string()));
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::AmountInput("1000".to_string()),
+        send::Message::SendCoinsSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    assert_eq!(spaced.calls("walletsendrequest").len(), 1);
+    assert_eq!(state.screen, Screen::Send);
+}
+
+#[tokio::test]
+async fn bids_on_space() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Space(slabel()))).await;
+    assert_eq!(spaced.calls("getspace").len(), 1);
+    for message in [
+        spaces::Message::AmountInput("5000".to_string()),
+        spaces::Message::BidSubmit,
+    ] {
+        dispatch(&mut state, Message::SpacesScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    assert!(calls[0][1].to_string().contains("example"));
+    assert_eq!(state.screen, Screen::Home);
+}
+
+#[tokio::test]
+async fn buys_listing() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Market)).await;
+    for message in [
+        market::Message::ListingAction(text_editor::Action::Edit(text_editor::Edit::Paste(
+            Arc::new(mock::listing().to_string()),
+        ))),
+        market::Message::FeeRateInput("1".to_string()),
+        market::Message::BuySubmit,
+    ] {
+        dispatch(&mut state, Message::MarketScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletbuy");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][1]["space"], json!("@example"));
+    assert_eq!(state.screen, Screen::Home);
+}
+
+#[tokio::test]
+async fn signs_event() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+    let event: NostrEvent = serde_json::from_value(json!({
+        "created_at": 0,
+        "kind": 1,
+        "tags": [],
+        "content": "hello",
+    }))
+    .unwrap();
+
+    dispatch(&mut state, Message::NavigateTo(Route::Sign)).await;
+    for message in [
+        sign::Message::EventFileLoaded(Ok(Some(("event.json".to_string(), event.clone())))),
+        sign::Message::SLabelSelect(slabel()),
+    ] {
+        dispatch(&mut state, Message::SignScreen(message)).await;
+    }
+    assert!(matches!(
+        state.sign_screen.update(sign::Message::SignSubmit),
+        sign::Action::Sign(..)
+    ));
+
+    // the main screen hands the signed event to a save dialog, so call the client directly
+    let results = mock::outputs(
+        state
+            .client
+            .sign_event(mock::WALLET.to_string(), slabel(), event),
+    )
+    .await;
+    assert!(results[0].result.is_ok());
+    let calls = spaced.calls("walletsignevent");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][1], json!("@example"));
+    assert_eq!(calls[0][2]["content"], json!("hello"));
+}
+
+#[tokio::test]
+async fn goes_offline_and_recovers() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    spaced.respond("getserverinfo", Err("connection refused".to_string()));
+    dispatch(&mut state, Message::Tick).await;
+    assert_eq!(state.connection.status(), ConnectionStatus::Degraded);
+    dispatch(&mut state, Message::Tick).await;
+    dispatch(&mut state, Message::Tick).await;
+    assert_eq!(state.connection.status(), ConnectionStatus::Offline);
+
+    spaced.restore("getserverinfo");
+    dispatch(&mut state, Message::Tick).await;
+    assert_ne!(state.connection.status(), ConnectionStatus::Offline);
+    assert!(!state.connection.is_failing());
+}
+
+#[tokio::test]
+async fn tracks_request_errors_by_kind() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    // a call spaced rejects is reported without counting against the connection
+    spaced.respond("walletgetbalance", Err("wallet is rescanning".to_string()));
+    dispatch(&mut state, Message::Tick).await;
+    assert_eq!(state.connection.status(), ConnectionStatus::Connected);
+    assert_eq!(
+        state.connection.call_error().map(String::as_str),
+        Some("wallet is rescanning")
+    );
+
+    let unreachable = RequestError::Transport("connection reset".to_string());
+    dispatch(&mut state, Message::SpaceInfo(Err(unreachable))).await;
+    assert_eq!(state.connection.status(), ConnectionStatus::Degraded);
+    // other requests succeeding don't clear the one that failed
+    spaced.restore("walletgetbalance");
+    dispatch(&mut state, Message::Tick).await;
+    assert_eq!(state.connection.status(), ConnectionStatus::Degraded);
+    assert!(state.connection.call_error().is_none());
+
+    dispatch(&mut state, Message::SpaceInfo(Ok((slabel(), None)))).await;
+    assert_eq!(state.connection.status(), ConnectionStatus::Connected);
+}
+
+#[tokio::test]
+async fn locks_after_timeout() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+    state.config.set_passphrase("hunter2").unwrap();
+    state.config.lock_timeout = Some(1);
+
+    dispatch(&mut state, Message::LockCheck).await;
+    assert!(!state.locked);
+
+    state.last_activity -= std::time::Duration::from_secs(61);
+    dispatch(&mut state, Message::LockCheck).await;
+    assert!(state.locked);
+
+    // polling keeps going while locked
+    let polls = spaced.calls("getserverinfo").len();
+    dispatch(&mut state, Message::Tick).await;
+    assert!(spaced.calls("getserverinfo").len() > polls);
+
+    for passphrase in ["wrong", "hunter2"] {
+        dispatch(
+            &mut state,
+            Message::LockScreen(lock::Message::PassphraseInput(passphrase.to_string())),
+        )
+        .await;
+        dispatch(&mut state, Message::LockScreen(lock::Message::UnlockSubmit)).await;
+        assert_eq!(state.locked, passphrase == "wrong");
+    }
+
+    dispatch(
+        &mut state,
+        Message::SettingsScreen(settings::Message::LockPress),
+    )
+    .await;
+    assert!(state.locked);
+}
+
+#[tokio::test]
+async fn sends_batch_payment() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    let csv = format!("address,amount\n{0},1000\n{0},2000\n", mock::ADDRESS);
+    for message in [
+        send::Message::CsvLoaded(Ok(Some(csv))),
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::AmountInput("3000".to_string()),
+        send::Message::SendCoinsSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    let requests = calls[0][1]["requests"].as_array().unwrap();
+    assert_eq!(requests.len(), 3);
+}
+
+#[tokio::test]
+async fn looks_up_listed_spaces_before_sending() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    let csv = format!("address,amount\n{},1000\n@example,2000\n", mock::ADDRESS);
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::CsvLoaded(Ok(Some(csv)))),
+    )
+    .await;
+    assert_eq!(spaced.calls("getspace").len(), 1);
+    let check = state
+        .send_screen
+        .check_payments(state.network, &state.spaces);
+    assert!(check.unwrap_err().ends_with("does not exist"));
+}
+
+#[tokio::test]
+async fn submits_composed_transaction() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(
+        &mut state,
+        Message::SpacesScreen(spaces::Message::QueuePress(TxRequest::Renew {
+            slabels: vec![slabel()],
+        })),
+    )
+    .await;
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::QueuePress(TxRequest::SendCoins {
+            to: mock::ADDRESS.to_string(),
+            amount: Amount::from_sat(1000),
+        })),
+    )
+    .await;
+    assert!(spaced.calls("walletsendrequest").is_empty());
+
+    dispatch(&mut state, Message::NavigateTo(Route::Composer)).await;
+    dispatch(
+        &mut state,
+        Message::ComposerScreen(composer::Message::Submit),
+    )
+    .await;
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][1]["requests"].as_array().unwrap().len(), 2);
+    assert!(state.composer_screen.is_empty());
+    assert_eq!(state.screen, Screen::Home);
+}
+
+#[tokio::test]
+async fn drops_composed_requests_on_wallet_switch() {
+    let spaced = MockSpaced::start().await;
+    spaced.respond("listwallets", Ok(json!([mock::WALLET, "other"])));
+    let mut state = start(&spaced).await;
+
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::QueuePress(TxRequest::SendCoins {
+            to: mock::ADDRESS.to_string(),
+            amount: Amount::from_sat(1000),
+        })),
+    )
+    .await;
+    assert!(!state.composer_screen.is_empty());
+
+    dispatch(
+        &mut state,
+        Message::SettingsScreen(settings::Message::WalletSelect("other".to_string())),
+    )
+    .await;
+    assert_eq!(state.wallets.get_current().unwrap().label, "other");
+    assert!(state.composer_screen.is_empty());
+}
+
+#[tokio::test]
+async fn renews_expiring_spaces_in_one_request() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    let slabels = vec![slabel(), SLabel::from_str_unprefixed("other").unwrap()];
+    for message in [
+        spaces::Message::FeeRateInput("5".to_string()),
+        spaces::Message::RenewExpiringPress(slabels),
+    ] {
+        dispatch(&mut state, Message::SpacesScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    let requests = calls[0][1]["requests"].as_array().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["spaces"], json!(["@example", "@other"]));
+    assert!(requests[0].get("to").is_none_or(|to| to.is_null()));
+    assert_eq!(
+        calls[0][1]["fee_rate"],
+        json!(FeeRate::from_sat_per_vb(5).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn keeps_space_dust_off_coin_sends() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::TabPress(AddressKind::Space),
+        send::Message::DustInput("700".to_string()),
+        send::Message::TabPress(AddressKind::Coin),
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::AmountInput("3000".to_string()),
+        send::Message::SendCoinsSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    assert!(calls[0][1]["dust"].is_null());
+}
+
+#[tokio::test]
+async fn transfers_selected_spaces_in_one_request() {
+    let spaced = MockSpaced::start().await;
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::SLabelToggle(slabel(), true),
+        send::Message::SLabelToggle(SLabel::from_str_unprefixed("other").unwrap(), true),
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::SendSpaceSubmit,
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    let requests = calls[0][1]["requests"].as_array().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["spaces"], json!(["@example", "@other"]));
+    assert_eq!(requests[0]["to"], json!(mock::ADDRESS));
+}
+
+#[tokio::test]
+async fn sends_maximum_without_space_outputs() {
+    let spaced = MockSpaced::start().await;
+    spaced.respond(
+        "walletlistunspent",
+        Ok(json!([
+            mock::unspent(0, 10_000, false, true),
+            mock::unspent(1, 5_000, false, false),
+            mock::unspent(2, 1_000, true, true),
+        ])),
+    );
+    let mut state = start(&spaced).await;
+
+    dispatch(&mut state, Message::NavigateTo(Route::Send)).await;
+    for message in [
+        send::Message::MaxToggle(true),
+        send::Message::RecipientInput(mock::ADDRESS.to_string()),
+        send::Message::FeeRateInput("1".to_string()),
+    ] {
+        dispatch(&mut state, Message::SendScreen(message)).await;
+    }
+    let unspent = &state.wallets.get_current().unwrap().state.unspent;
+    assert_eq!(unspent.len(), 3);
+    // two p2wpkh coins in, one output of any type: 11 + 2 * 69 + 43 vB at 1 sat/vB
+    let (amount, fee_rate) = state.send_screen.max_payment(unspent, None).unwrap();
+    assert_eq!(amount, Amount::from_sat(15_000 - 192));
+
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::ConfirmedOnlyToggle(true)),
+    )
+    .await;
+    let unspent = &state.wallets.get_current().unwrap().state.unspent;
+    let (amount, _) = state.send_screen.max_payment(unspent, None).unwrap();
+    assert_eq!(amount, Amount::from_sat(10_000 - 123));
+
+    // an empty fee rate works out the maximum at the rate it will be sent with
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::FeeRateInput(String::new())),
+    )
+    .await;
+    let unspent = &state.wallets.get_current().unwrap().state.unspent;
+    assert!(state.send_screen.max_payment(unspent, None).is_err());
+    let estimates = FeeEstimates {
+        fast: 13,
+        normal: 5,
+        slow: 1,
+    };
+    assert_eq!(
+        state.send_screen.max_payment(unspent, Some(estimates)),
+        Ok((Amount::from_sat(10_000 - 5 * 123), estimates.auto()))
+    );
+
+    dispatch(
+        &mut state,
+        Message::SendScreen(send::Message::SendMaxSubmit(amount, fee_rate)),
+    )
+    .await;
+    confirm(&mut state).await;
+
+    let calls = spaced.calls("walletsendrequest");
+    assert_eq!(calls.len(), 1);
+    let requests = calls[0][1]["requests"].as_array().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["amount"], json!(amount));
+    assert_eq!(requests[0]["to"], json!(mock::ADDRESS));
+    assert_eq!(calls[0][1]["confirmed_only"], json!(true));
+}
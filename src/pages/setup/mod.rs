@@ -1,39 +1,66 @@
 use iced::{
-    Center, Element, Task,
+    Center, Element, Fill, Task,
     widget::{button, column, container, horizontal_space, row},
 };
+use std::time::Duration;
 
 use spaces_client::config::ExtendedNetwork;
 
 use crate::{
     Config, ConfigBackend,
-    client::{Client, ClientResult, ServerInfo},
+    client::{
+        Client, ClientOptions, ClientResult, RequestResult, ServerInfo, bitcoind_chain, check_proxy,
+    },
+    spaced::{Spaced, Status as NodeStatus},
     widget::{
-        form::{Form, submit_button},
+        form::{Form, pick_list, submit_button},
         icon::{Icon, button_icon, text_icon},
-        text::{error_block, text_big, text_bold},
+        text::{error_block, text_big, text_bold, text_small},
     },
 };
 
+/// How long to wait for a starting node to answer, one attempt per second.
+const MAX_CONNECT_ATTEMPTS: u32 = 120;
+
 #[derive(Debug)]
 pub struct State {
     config: Config,
     client: Option<Client>,
+    node: Option<Spaced>,
+    profile_name: String,
+    passphrase: String,
     connected: bool,
+    /// A managed node is still shutting down, connecting waits for it to exit.
+    stopping: bool,
+    /// Failed attempts so far and the last error while the node is starting.
+    retry: Option<(u32, String)>,
     error: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    PassphraseInput(String),
+    Unlock,
+    ForgetSecrets,
+    ProfileSelect(String),
     BackendSet(ConfigBackend),
+    ProfileNameInput(String),
     NetworkSelect(ExtendedNetwork),
     UrlInput(String),
     CookieInput(String),
     UserInput(String),
     PasswordInput(String),
+    TokenInput(String),
+    CaCertInput(String),
+    FingerprintInput(String),
+    ProxyInput(String),
     Connect,
+    ProxyResult(ClientResult<()>),
+    BitcoindResult(ClientResult<String>),
+    ConnectRetry,
+    NodeStopped,
     ConnectResult(ClientResult<ServerInfo>),
-    ListWalletsResult(ClientResult<Vec<String>>),
+    ListWalletsResult(RequestResult<Vec<String>>),
     Reset,
     Disconnect,
     CreateWallet,
@@ -43,7 +70,7 @@ pub enum Message {
 }
 
 pub enum Action {
-    Return(Config, Client),
+    Return(Config, Client, Option<Spaced>),
     Task(Task<Message>),
 }
 
@@ -54,35 +81,136 @@ impl Action {
 }
 
 impl State {
-    pub fn run(config: Config) -> (Self, Task<Message>) {
-        let task = if config.backend.is_some() {
+    pub fn run(mut config: Config) -> (Self, Task<Message>) {
+        let task = if config.backend.is_some() && !config.is_locked() {
             Task::done(Message::Connect)
         } else {
             Task::none()
         };
+        let profile_name = config.profile.clone().unwrap_or_default();
+        let error = config.take_load_error();
         (
             Self {
                 config,
                 client: None,
+                node: None,
+                profile_name,
+                passphrase: String::new(),
                 connected: false,
-                error: None,
+                stopping: false,
+                retry: None,
+                error,
             },
             task,
         )
     }
 
     fn finish(&mut self) -> Action {
-        self.config.save();
-        Action::Return(self.config.clone(), self.client.take().unwrap())
+        let name = if self.profile_name.is_empty() {
+            self.config.backend.as_ref().unwrap().default_profile_name()
+        } else {
+            self.profile_name.clone()
+        };
+        self.config.set_profile_name(name);
+        match self.config.save() {
+            // an unreadable config was reported on load, the session goes on without saving
+            Err(_) if self.config.is_ephemeral() => {}
+            Err(err) => {
+                self.error = Some(err);
+                return if self.connected {
+                    Action::none()
+                } else {
+                    Action::Task(self.disconnect())
+                };
+            }
+            Ok(()) => {}
+        }
+        Action::Return(
+            self.config.clone(),
+            self.client.take().unwrap(),
+            self.node.take(),
+        )
+    }
+
+    fn connect(&mut self, client: Result<Client, String>) -> Action {
+        match client {
+            Ok(client) => {
+                let task = client.get_server_info().map(Message::ConnectResult);
+                self.client = Some(client);
+                Action::Task(task)
+            }
+            Err(err) => Action::Task(Task::done(Message::ConnectResult(Err(err)))),
+        }
+    }
+
+    fn disconnect(&mut self) -> Task<Message> {
+        self.client = None;
+        self.retry = None;
+        match self.node.take() {
+            Some(node) => {
+                self.stopping = true;
+                Task::future(node.shutdown()).map(|_| Message::NodeStopped)
+            }
+            None => Task::none(),
+        }
+    }
+
+    fn can_connect(&self) -> bool {
+        self.client.is_none() && !self.stopping
+    }
+
+    fn spaced_client(&self) -> Result<Client, String> {
+        self.config.backend.as_ref().unwrap().connect()
+    }
+
+    pub fn node(&self) -> Option<&Spaced> {
+        self.node.as_ref()
     }
 
     pub fn update(&mut self, message: Message) -> Action {
         self.error = None;
         match message {
+            Message::PassphraseInput(value) => {
+                self.passphrase = value;
+                Action::none()
+            }
+            Message::Unlock => match self.config.unlock(&self.passphrase) {
+                Ok(()) => {
+                    self.passphrase = String::new();
+                    if self.config.backend.is_some() {
+                        Action::Task(Task::done(Message::Connect))
+                    } else {
+                        Action::none()
+                    }
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    Action::none()
+                }
+            },
+            Message::ForgetSecrets => {
+                if let Err(err) = self.config.forget_secrets() {
+                    self.error = Some(err);
+                }
+                Action::none()
+            }
+            Message::ProfileSelect(name) => {
+                self.config.select_profile(&name);
+                self.profile_name = name;
+                if self.stopping {
+                    Action::none()
+                } else {
+                    Action::Task(Task::done(Message::Connect))
+                }
+            }
             Message::BackendSet(value) => {
                 self.config.backend = Some(value);
                 Action::none()
             }
+            Message::ProfileNameInput(value) => {
+                self.profile_name = value;
+                Action::none()
+            }
             Message::NetworkSelect(value) => {
                 match self.config.backend.as_mut() {
                     Some(ConfigBackend::Embedded { network })
@@ -109,33 +237,131 @@ impl State {
             }
             Message::UserInput(value) => {
                 match self.config.backend.as_mut() {
-                    Some(ConfigBackend::Bitcoind { user, .. }) => *user = value,
+                    Some(ConfigBackend::Bitcoind { user, .. })
+                    | Some(ConfigBackend::Spaced { user, .. }) => *user = value,
                     _ => unreachable!(),
                 }
                 Action::none()
             }
             Message::PasswordInput(value) => {
                 match self.config.backend.as_mut() {
-                    Some(ConfigBackend::Bitcoind { password, .. }) => *password = value,
+                    Some(ConfigBackend::Bitcoind { password, .. })
+                    | Some(ConfigBackend::Spaced { password, .. }) => *password = value,
+                    _ => unreachable!(),
+                }
+                Action::none()
+            }
+            Message::TokenInput(value) => {
+                match self.config.backend.as_mut() {
+                    Some(ConfigBackend::Spaced { token, .. }) => *token = value,
                     _ => unreachable!(),
                 }
                 Action::none()
             }
+            Message::CaCertInput(value) => {
+                match self.config.backend.as_mut() {
+                    Some(ConfigBackend::Spaced { ca_cert, .. }) => *ca_cert = value,
+                    _ => unreachable!(),
+                }
+                Action::none()
+            }
+            Message::FingerprintInput(value) => {
+                match self.config.backend.as_mut() {
+                    Some(ConfigBackend::Spaced { fingerprint, .. }) => *fingerprint = value,
+                    _ => unreachable!(),
+                }
+                Action::none()
+            }
+            Message::ProxyInput(value) => {
+                match self.config.backend.as_mut() {
+                    Some(ConfigBackend::Spaced { proxy, .. }) => *proxy = value,
+                    _ => unreachable!(),
+                }
+                Action::none()
+            }
+            Message::Connect if !self.can_connect() => Action::none(),
             Message::Connect => match self.config.backend.as_ref() {
-                Some(ConfigBackend::Embedded { .. }) => unimplemented!(),
-                Some(ConfigBackend::Bitcoind { .. }) => unimplemented!(),
-                Some(ConfigBackend::Spaced { url, .. }) => match Client::new(url) {
-                    Ok(client) => {
-                        let task = client.get_server_info().map(Message::ConnectResult);
-                        self.client = Some(client);
-                        Action::Task(task)
+                Some(ConfigBackend::Embedded { network }) => {
+                    let data_dir = self.config.data_dir().join("spaced");
+                    match Spaced::start_embedded(&data_dir, *network) {
+                        Ok(node) => {
+                            let client = Client::new(node.rpc_url(), ClientOptions::default());
+                            self.node = Some(node);
+                            self.connect(client)
+                        }
+                        Err(err) => Action::Task(Task::done(Message::ConnectResult(Err(err)))),
                     }
-                    Err(err) => Action::Task(Task::done(Message::ConnectResult(Err(err)))),
+                }
+                Some(backend @ ConfigBackend::Bitcoind { .. }) => match backend.bitcoind().unwrap()
+                {
+                    Ok(bitcoind) => Action::Task(bitcoind.get_chain().map(Message::BitcoindResult)),
+                    Err(err) => Action::Task(Task::done(Message::BitcoindResult(Err(err)))),
                 },
+                Some(ConfigBackend::Spaced { url, proxy, .. }) if !proxy.is_empty() => {
+                    Action::Task(check_proxy(proxy, url).map(Message::ProxyResult))
+                }
+                Some(ConfigBackend::Spaced { .. }) => {
+                    let client = self.spaced_client();
+                    self.connect(client)
+                }
                 _ => unreachable!(),
             },
+            Message::ProxyResult(result) => match result {
+                Ok(()) => {
+                    let client = self.spaced_client();
+                    self.connect(client)
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    Action::none()
+                }
+            },
+            Message::BitcoindResult(result) => match self.config.backend.as_ref() {
+                Some(ConfigBackend::Bitcoind {
+                    network,
+                    url,
+                    cookie,
+                    user,
+                    password,
+                }) => match result {
+                    Ok(chain) if chain == bitcoind_chain(*network) => {
+                        let data_dir = self.config.data_dir().join("spaced-bitcoind");
+                        match Spaced::start_bitcoind(
+                            &data_dir, *network, url, cookie, user, password,
+                        ) {
+                            Ok(node) => {
+                                let client = Client::new(node.rpc_url(), ClientOptions::default());
+                                self.node = Some(node);
+                                self.connect(client)
+                            }
+                            Err(err) => {
+                                self.error = Some(err);
+                                Action::none()
+                            }
+                        }
+                    }
+                    Ok(chain) => {
+                        self.error = Some(format!("Bitcoind is running on {} chain", chain));
+                        Action::none()
+                    }
+                    Err(err) => {
+                        self.error = Some(err);
+                        Action::none()
+                    }
+                },
+                _ => Action::none(),
+            },
+            Message::NodeStopped => {
+                self.stopping = false;
+                Action::none()
+            }
+            Message::ConnectRetry => match self.client.as_ref() {
+                Some(client) => Action::Task(client.get_server_info().map(Message::ConnectResult)),
+                None => Action::none(),
+            },
             Message::ConnectResult(result) => match result {
                 Ok(info) => {
+                    self.retry = None;
                     let network = match self.config.backend.as_ref() {
                         Some(ConfigBackend::Embedded { network, .. })
                         | Some(ConfigBackend::Bitcoind { network, .. })
@@ -143,7 +369,6 @@ impl State {
                         _ => unreachable!(),
                     };
                     if info.network == network.to_string() {
-                        self.config.wallet = None;
                         Action::Task(
                             self.client
                                 .as_ref()
@@ -152,16 +377,36 @@ impl State {
                                 .map(Message::ListWalletsResult),
                         )
                     } else {
-                        self.client = None;
                         self.error = Some("Wrong network".to_string());
-                        Action::none()
+                        Action::Task(self.disconnect())
                     }
                 }
-                Err(err) => {
-                    self.client = None;
-                    self.error = Some(err);
-                    Action::none()
-                }
+                Err(err) => match self.node.as_ref().map(|node| node.status()) {
+                    Some(NodeStatus::Running) | Some(NodeStatus::Restarting { .. }) => {
+                        let attempt = self.retry.as_ref().map_or(1, |(attempt, _)| attempt + 1);
+                        if attempt > MAX_CONNECT_ATTEMPTS {
+                            self.error = Some(format!(
+                                "Spaced did not answer after {} attempts: {}",
+                                MAX_CONNECT_ATTEMPTS, err
+                            ));
+                            Action::Task(self.disconnect())
+                        } else {
+                            self.retry = Some((attempt, err));
+                            Action::Task(Task::perform(
+                                tokio::time::sleep(Duration::from_secs(1)),
+                                |_| Message::ConnectRetry,
+                            ))
+                        }
+                    }
+                    Some(NodeStatus::Failed(node_err)) => {
+                        self.error = Some(node_err);
+                        Action::Task(self.disconnect())
+                    }
+                    Some(NodeStatus::Stopped) | None => {
+                        self.error = Some(err);
+                        Action::Task(self.disconnect())
+                    }
+                },
             },
             Message::ListWalletsResult(result) => match result {
                 Ok(wallets) => {
@@ -169,6 +414,14 @@ impl State {
                         self.connected = true;
                         Action::none()
                     } else {
+                        if self
+                            .config
+                            .wallet
+                            .as_ref()
+                            .is_some_and(|wallet| !wallets.contains(wallet))
+                        {
+                            self.config.wallet = None;
+                        }
                         if self.config.wallet.is_none() && wallets.contains(&"default".to_string())
                         {
                             self.config.wallet = Some("default".to_string());
@@ -177,21 +430,19 @@ impl State {
                     }
                 }
                 Err(err) => {
-                    self.client = None;
-                    self.error = Some(err);
-                    Action::none()
+                    self.error = Some(err.to_string());
+                    Action::Task(self.disconnect())
                 }
             },
             Message::Reset => {
-                self.config.backend = None;
-                self.client = None;
+                self.config.new_profile();
+                self.profile_name = String::new();
                 self.connected = false;
-                Action::none()
+                Action::Task(self.disconnect())
             }
             Message::Disconnect => {
-                self.client = None;
                 self.connected = false;
-                Action::none()
+                Action::Task(self.disconnect())
             }
             Message::CreateWallet => Action::Task(
                 self.client
@@ -242,7 +493,30 @@ impl State {
     }
 
     pub fn view(&self) -> Element<Message> {
-        container(if self.config.backend.is_none() {
+        container(if self.config.is_locked() {
+            column![
+                text_big("Unlock"),
+                error_block(self.error.as_ref()),
+                Form::new(
+                    "Unlock",
+                    (!self.passphrase.is_empty()).then_some(Message::Unlock),
+                )
+                .add_secure_input(
+                    "Master passphrase",
+                    "",
+                    &self.passphrase,
+                    Message::PassphraseInput,
+                ),
+                row![
+                    horizontal_space(),
+                    button("Forgot passphrase? Clear saved credentials")
+                        .style(button::text)
+                        .on_press(Message::ForgetSecrets),
+                    horizontal_space(),
+                ],
+            ]
+            .spacing(10)
+        } else if self.config.backend.is_none() {
             column![
                 text_big("Select backend"),
                 row![
@@ -282,6 +556,12 @@ impl State {
                             Some(Message::BackendSet(ConfigBackend::Spaced {
                                 network: ExtendedNetwork::Mainnet,
                                 url: "http://127.0.0.1:7225".to_string(),
+                                user: String::new(),
+                                password: String::new(),
+                                token: String::new(),
+                                ca_cert: String::new(),
+                                fingerprint: String::new(),
+                                proxy: String::new(),
                             }))
                         ),
                     ]
@@ -290,6 +570,18 @@ impl State {
                 ]
                 .spacing(200),
             ]
+            .push_maybe({
+                let profiles = self.config.profiles();
+                (!profiles.is_empty()).then(|| {
+                    column![
+                        text_big("Saved profiles"),
+                        pick_list(profiles, None::<&String>, |name| {
+                            Message::ProfileSelect(name.to_string())
+                        }),
+                    ]
+                    .spacing(10)
+                })
+            })
             .spacing(10)
         } else if !self.connected {
             column![
@@ -307,10 +599,10 @@ impl State {
                         ExtendedNetwork::Testnet4,
                         ExtendedNetwork::Regtest,
                     ];
-                    match self.config.backend.as_ref().unwrap() {
+                    let form = match self.config.backend.as_ref().unwrap() {
                         ConfigBackend::Embedded { network } => Form::new(
                             "Connect",
-                            if self.client.is_none() {
+                            if self.can_connect() {
                                 Some(Message::Connect)
                             } else {
                                 None
@@ -325,7 +617,7 @@ impl State {
                             password,
                         } => Form::new(
                             "Connect",
-                            if self.client.is_none() && !url.is_empty() {
+                            if self.can_connect() && !url.is_empty() {
                                 Some(Message::Connect)
                             } else {
                                 None
@@ -333,22 +625,31 @@ impl State {
                         )
                         .add_text_input(
                             "Bitcoind JSON-RPC URL",
-                            "http://127.0.0.1:7225",
+                            "http://127.0.0.1:8332",
                             url,
                             Message::UrlInput,
                         )
-                        .add_text_input("Auth cookie", "none", cookie, Message::CookieInput)
+                        .add_text_input("Auth cookie file", "none", cookie, Message::CookieInput)
                         .add_text_input("User login", "none", user, Message::UserInput)
-                        .add_text_input("User password", "none", password, Message::PasswordInput)
+                        .add_secure_input("User password", "none", password, Message::PasswordInput)
                         .add_pick_list(
                             "Chain",
                             networks,
                             Some(network),
                             Message::NetworkSelect,
                         ),
-                        ConfigBackend::Spaced { network, url } => Form::new(
+                        ConfigBackend::Spaced {
+                            network,
+                            url,
+                            user,
+                            password,
+                            token,
+                            ca_cert,
+                            fingerprint,
+                            proxy,
+                        } => Form::new(
                             "Connect",
-                            if self.client.is_none() && !url.is_empty() {
+                            if self.can_connect() && !url.is_empty() {
                                 Some(Message::Connect)
                             } else {
                                 None
@@ -356,14 +657,59 @@ impl State {
                         )
                         .add_text_input(
                             "Spaced JSON-RPC URL",
-                            "http://127.0.0.1:8332",
+                            "http://127.0.0.1:7225",
                             url,
                             Message::UrlInput,
                         )
-                        .add_pick_list("Chain", networks, Some(network), Message::NetworkSelect),
-                    }
+                        .add_text_input("User login", "none", user, Message::UserInput)
+                        .add_secure_input("User password", "none", password, Message::PasswordInput)
+                        .add_secure_input("Bearer token", "none", token, Message::TokenInput)
+                        .add_text_input(
+                            "CA certificate file",
+                            "none",
+                            ca_cert,
+                            Message::CaCertInput,
+                        )
+                        .add_text_input(
+                            "Certificate SHA-256 fingerprint",
+                            "none",
+                            fingerprint,
+                            Message::FingerprintInput,
+                        )
+                        .add_text_input("SOCKS5 proxy", "none", proxy, Message::ProxyInput)
+                        .add_pick_list(
+                            "Chain",
+                            networks,
+                            Some(network),
+                            Message::NetworkSelect,
+                        ),
+                    };
+                    form.add_text_input(
+                        "Profile name",
+                        "auto",
+                        &self.profile_name,
+                        Message::ProfileNameInput,
+                    )
                 },
             ]
+            .push_maybe(self.client.as_ref().map(|_| {
+                row![
+                    text_small(match self.retry.as_ref() {
+                        Some((attempt, err)) => format!(
+                            "Waiting for spaced to start, attempt {} of {}: {}",
+                            attempt, MAX_CONNECT_ATTEMPTS, err
+                        ),
+                        None => "Connecting...".to_string(),
+                    })
+                    .width(Fill),
+                    button("Cancel")
+                        .style(button::secondary)
+                        .on_press(Message::Disconnect),
+                ]
+                .spacing(10)
+                .align_y(Center)
+            }))
+            .push_maybe(self.stopping.then(|| text_small("Stopping spaced...")))
             .spacing(10)
         } else {
             column![
@@ -401,3 +747,6 @@ impl State {
         .into()
     }
 }
+
+#[cfg(test)]
+mod tests;
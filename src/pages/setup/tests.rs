@@ -0,0 +1,76 @@
+use iced::Task;
+use serde_json::json;
+use std::collections::VecDeque;
+
+use spaces_client::config::ExtendedNetwork;
+
+use super::{Action, Message, State};
+use crate::{
+    Config, ConfigBackend,
+    client::Client,
+    mock::{self, MockSpaced},
+};
+
+async fn drive(state: &mut State, task: Task<Message>) -> Option<(Config, Client)> {
+    let mut queue: VecDeque<Message> = mock::outputs(task).await.into();
+    while let Some(message) = queue.pop_front() {
+        match state.update(message) {
+            Action::Return(config, client, _) => return Some((config, client)),
+            Action::Task(task) => queue.extend(mock::outputs(task).await),
+        }
+    }
+    None
+}
+
+#[tokio::test]
+async fn connects_and_picks_default_wallet() {
+    let spaced = MockSpaced::start().await;
+    let (mut state, task) = State::run(spaced.config());
+
+    let (config, _) = drive(&mut state, task).await.expect("setup should finish");
+
+    assert_eq!(config.wallet.as_deref(), Some(mock::WALLET));
+    assert_eq!(spaced.calls("getserverinfo").len(), 1);
+    assert_eq!(spaced.calls("listwallets").len(), 1);
+}
+
+#[tokio::test]
+async fn rejects_wrong_network() {
+    let spaced = MockSpaced::start().await;
+    let mut config = spaced.config();
+    if let Some(ConfigBackend::Spaced { network, .. }) = config.backend.as_mut() {
+        *network = ExtendedNetwork::Mainnet;
+    }
+    let (mut state, task) = State::run(config);
+
+    assert!(drive(&mut state, task).await.is_none());
+    assert_eq!(state.error.as_deref(), Some("Wrong network"));
+    assert!(state.client.is_none());
+    assert!(spaced.calls("listwallets").is_empty());
+}
+
+#[tokio::test]
+async fn reports_rpc_errors() {
+    let spaced = MockSpaced::start().await;
+    spaced.respond("getserverinfo", Err("spaced is syncing".to_string()));
+    let (mut state, task) = State::run(spaced.config());
+
+    assert!(drive(&mut state, task).await.is_none());
+    assert_eq!(state.error.as_deref(), Some("spaced is syncing"));
+}
+
+#[tokio::test]
+async fn creates_wallet_when_none_exist() {
+    let spaced = MockSpaced::start().await;
+    spaced.respond("listwallets", Ok(json!([])));
+    let (mut state, task) = State::run(spaced.config());
+
+    assert!(drive(&mut state, task).await.is_none());
+    assert!(state.connected);
+
+    let (config, _) = drive(&mut state, Task::done(Message::CreateWallet))
+        .await
+        .expect("setup should finish");
+    assert_eq!(config.wallet.as_deref(), Some(mock::WALLET));
+    assert_eq!(spaced.calls("walletcreate"), vec![json!([mock::WALLET])]);
+}
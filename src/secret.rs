@@ -0,0 +1,61 @@
+use argon2::Argon2;
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, rand_core::RngCore},
+};
+use std::fmt;
+
+const PREFIX: &str = "enc:";
+const NONCE_LEN: usize = 12;
+
+/// Key derived from the master passphrase, used to encrypt credentials in the config.
+#[derive(Clone)]
+pub struct MasterKey(Key);
+
+impl fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MasterKey(..)")
+    }
+}
+
+pub fn new_salt() -> String {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    hex::encode(salt)
+}
+
+impl MasterKey {
+    pub fn derive(passphrase: &str, salt: &str) -> Result<Self, String> {
+        let salt = hex::decode(salt).map_err(|e| e.to_string())?;
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| e.to_string())?;
+        Ok(Self(key.into()))
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.0)
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("encryption does not fail for in-memory buffers");
+        format!(
+            "{}{}",
+            PREFIX,
+            hex::encode([nonce.as_slice(), &ciphertext].concat())
+        )
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, String> {
+        let data = value
+            .strip_prefix(PREFIX)
+            .and_then(|data| hex::decode(data).ok())
+            .filter(|data| data.len() > NONCE_LEN)
+            .ok_or_else(|| "Malformed encrypted value".to_string())?;
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = ChaCha20Poly1305::new(&self.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Wrong passphrase".to_string())?;
+        String::from_utf8(plaintext).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::{mpsc, watch},
+};
+
+use spaces_client::config::ExtendedNetwork;
+
+const LOG_CAPACITY: usize = 1000;
+const MAX_RESTARTS: u32 = 5;
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    Restarting { attempt: u32 },
+    Stopped,
+    Failed(String),
+}
+
+#[derive(Debug)]
+enum Control {
+    Restart,
+    Shutdown,
+}
+
+type Logs = Arc<Mutex<VecDeque<String>>>;
+
+#[derive(Debug, Clone)]
+pub struct Spaced {
+    rpc_url: String,
+    logs: Logs,
+    status: watch::Receiver<Status>,
+    control: mpsc::UnboundedSender<Control>,
+}
+
+#[derive(Debug)]
+struct Launch {
+    program: PathBuf,
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+}
+
+fn binary_path() -> PathBuf {
+    let name = if cfg!(windows) {
+        "spaced.exe"
+    } else {
+        "spaced"
+    };
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(name))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+fn rpc_port(network: ExtendedNetwork) -> u16 {
+    match network {
+        ExtendedNetwork::Mainnet => 7225,
+        ExtendedNetwork::Testnet4 => 7224,
+        ExtendedNetwork::Regtest => 7218,
+        _ => 7221,
+    }
+}
+
+pub fn rpc_url(network: ExtendedNetwork) -> String {
+    format!("http://127.0.0.1:{}", rpc_port(network))
+}
+
+fn push_log(logs: &Logs, line: String) {
+    let mut logs = logs.lock().unwrap();
+    if logs.len() == LOG_CAPACITY {
+        logs.pop_front();
+    }
+    logs.push_back(line);
+}
+
+async fn capture(stream: impl AsyncRead + Unpin, logs: Logs) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        push_log(&logs, line);
+    }
+}
+
+async fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // give spaced a chance to flush its databases before resorting to SIGKILL
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, child.wait())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+    }
+    let _ = child.kill().await;
+}
+
+impl Launch {
+    fn spawn(&self, logs: &Logs) -> Result<Child, String> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .envs(self.envs.iter().cloned())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start spaced: {}", e))?;
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(capture(stdout, logs.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(capture(stderr, logs.clone()));
+        }
+        Ok(child)
+    }
+}
+
+async fn supervise(
+    launch: Launch,
+    mut child: Child,
+    logs: Logs,
+    status: watch::Sender<Status>,
+    mut control: mpsc::UnboundedReceiver<Control>,
+) {
+    let mut attempt = 0;
+    loop {
+        let started = Instant::now();
+        let restart_requested = tokio::select! {
+            result = child.wait() => {
+                push_log(&logs, match result {
+                    Ok(exit_status) => format!("spaced exited with {}", exit_status),
+                    Err(err) => format!("spaced failed: {}", err),
+                });
+                false
+            }
+            command = control.recv() => {
+                terminate(&mut child).await;
+                if matches!(command, Some(Control::Restart)) {
+                    true
+                } else {
+                    status.send_replace(Status::Stopped);
+                    return;
+                }
+            }
+        };
+
+        if restart_requested {
+            attempt = 0;
+        } else {
+            if started.elapsed() >= STABLE_UPTIME {
+                attempt = 0;
+            }
+            attempt += 1;
+            let backoff = if attempt > MAX_RESTARTS {
+                status.send_replace(Status::Failed(
+                    "spaced keeps crashing, see the node log".to_string(),
+                ));
+                None
+            } else {
+                status.send_replace(Status::Restarting { attempt });
+                Some(Duration::from_secs(1 << attempt))
+            };
+            tokio::select! {
+                _ = async {
+                    match backoff {
+                        Some(backoff) => tokio::time::sleep(backoff).await,
+                        None => std::future::pending().await,
+                    }
+                } => {}
+                command = control.recv() => {
+                    if matches!(command, Some(Control::Restart)) {
+                        attempt = 0;
+                    } else {
+                        status.send_replace(Status::Stopped);
+                        return;
+                    }
+                }
+            }
+        }
+
+        child = loop {
+            match launch.spawn(&logs) {
+                Ok(child) => break child,
+                Err(err) => {
+                    push_log(&logs, err.clone());
+                    status.send_replace(Status::Failed(err));
+                    if !matches!(control.recv().await, Some(Control::Restart)) {
+                        status.send_replace(Status::Stopped);
+                        return;
+                    }
+                }
+            }
+        };
+        status.send_replace(Status::Running);
+    }
+}
+
+impl Spaced {
+    fn spawn(
+        data_dir: &Path,
+        network: ExtendedNetwork,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<Self, String> {
+        std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        let port = rpc_port(network);
+        let mut launch_args = vec![
+            "--chain".to_string(),
+            network.to_string(),
+            "--data-dir".to_string(),
+            data_dir.to_string_lossy().into_owned(),
+            "--rpc-bind".to_string(),
+            "127.0.0.1".to_string(),
+            "--rpc-port".to_string(),
+            port.to_string(),
+        ];
+        launch_args.extend(args.iter().map(|arg| arg.to_string()));
+        let launch = Launch {
+            program: binary_path(),
+            args: launch_args,
+            envs: envs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+
+        let logs = Logs::default();
+        let child = launch.spawn(&logs)?;
+        let (status_sender, status) = watch::channel(Status::Running);
+        let (control, control_receiver) = mpsc::unbounded_channel();
+        tokio::spawn(supervise(
+            launch,
+            child,
+            logs.clone(),
+            status_sender,
+            control_receiver,
+        ));
+
+        Ok(Self {
+            rpc_url: rpc_url(network),
+            logs,
+            status,
+            control,
+        })
+    }
+
+    pub fn start_embedded(data_dir: &Path, network: ExtendedNetwork) -> Result<Self, String> {
+        Self::spawn(data_dir, network, &["--bitcoin-rpc-light"], &[])
+    }
+
+    pub fn start_bitcoind(
+        data_dir: &Path,
+        network: ExtendedNetwork,
+        url: &str,
+        cookie: &str,
+        user: &str,
+        password: &str,
+    ) -> Result<Self, String> {
+        if !cookie.is_empty() {
+            Self::spawn(
+                data_dir,
+                network,
+                &["--bitcoin-rpc-url", url, "--bitcoin-rpc-cookie", cookie],
+                &[],
+            )
+        } else if !user.is_empty() {
+            // credentials are passed through the environment to keep them out of the process list
+            Self::spawn(
+                data_dir,
+                network,
+                &["--bitcoin-rpc-url", url],
+                &[
+                    ("SPACED_BITCOIN_RPC_USER", user),
+                    ("SPACED_BITCOIN_RPC_PASSWORD", password),
+                ],
+            )
+        } else {
+            Self::spawn(data_dir, network, &["--bitcoin-rpc-url", url], &[])
+        }
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    pub fn status(&self) -> Status {
+        self.status.borrow().clone()
+    }
+
+    pub fn logs(&self) -> Vec<String> {
+        self.logs.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn restart(&self) {
+        let _ = self.control.send(Control::Restart);
+    }
+
+    pub fn stop(&self) {
+        let _ = self.control.send(Control::Shutdown);
+    }
+
+    pub fn shutdown(&self) -> impl Future<Output = ()> + 'static {
+        self.stop();
+        let mut status = self.status.clone();
+        async move {
+            let _ = status
+                .wait_for(|status| matches!(status, Status::Stopped | Status::Failed(_)))
+                .await;
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use serde_json::json;
+use std::fs;
+
+use spaces_client::config::ExtendedNetwork;
+
+use crate::{
+    CONFIG_VERSION, Config, ConfigBackend, Overrides,
+    client::{BitcoindClient, Client, ClientOptions, FeeEstimates},
+    contacts::{AddressBook, ContactsFormat},
+    helpers::{
+        Amount, format_amount, is_recipient_input, is_recipient_on_network, payments_from_csv,
+        recipient_from_str,
+    },
+    mock::{self, TempDir},
+};
+
+#[test]
+fn migrates_unversioned_config() {
+    let dir = TempDir::new();
+    let path = dir.path().join("config.json");
+    let backend = ConfigBackend::Embedded {
+        network: ExtendedNetwork::Regtest,
+    };
+    fs::write(
+        &path,
+        json!({ "backend": backend, "wallet": "default" }).to_string(),
+    )
+    .unwrap();
+
+    let config = Config::load(path);
+
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert!(config.load_error.is_none());
+    assert_eq!(config.wallet.as_deref(), Some("default"));
+    assert_eq!(config.profiles(), vec!["regtest embedded"]);
+    assert!(matches!(
+        config.backend,
+        Some(ConfigBackend::Embedded {
+            network: ExtendedNetwork::Regtest
+        })
+    ));
+}
+
+#[test]
+fn migrates_config_before_encryption() {
+    let dir = TempDir::new();
+    let path = dir.path().join("config.json");
+    fs::write(
+        &path,
+        json!({ "version": 1, "backend": null, "wallet": "default" }).to_string(),
+    )
+    .unwrap();
+
+    let mut config = Config::load(path.clone());
+    assert!(config.load_error.is_none());
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert!(!config.has_passphrase());
+    assert_eq!(config.lock_timeout, None);
+    assert!(!config.hide_amounts);
+
+    config.save().unwrap();
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["version"], json!(CONFIG_VERSION));
+}
+
+#[test]
+fn backs_up_unreadable_config() {
+    let dir = TempDir::new();
+    let path = dir.path().join("config.json");
+    fs::write(&path, "{ not json").unwrap();
+
+    let mut config = Config::load(path.clone());
+
+    assert!(config.backend.is_none());
+    assert!(config.take_load_error().is_some());
+    assert!(!path.exists());
+    let backups = fs::read_dir(dir.path())
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .ends_with(".bak")
+        })
+        .count();
+    assert_eq!(backups, 1);
+}
+
+#[test]
+fn saves_atomically() {
+    let dir = TempDir::new();
+    let mut config = mock::config(&dir, "http://127.0.0.1:7218");
+    config.save().unwrap();
+
+    let loaded = Config::load(config.path.clone());
+    assert!(loaded.backend.is_some());
+    assert!(!config.path.with_extension("json.tmp").exists());
+}
+
+#[test]
+fn reports_unusable_config_dir() {
+    let dir = TempDir::new();
+    let file = dir.path().join("file");
+    fs::write(&file, "").unwrap();
+
+    let mut config = Config::load_with(&Overrides {
+        config: Some(file.join("config.json")),
+        ..Default::default()
+    });
+
+    assert!(config.take_load_error().is_some());
+    assert!(config.save().is_err());
+    assert!(config.set_passphrase("hunter2").is_err());
+    assert!(!config.has_passphrase());
+}
+
+#[test]
+fn keeps_overrides_out_of_saved_config() {
+    let dir = TempDir::new();
+    let mut config = mock::config(&dir, "http://127.0.0.1:7218");
+    config.wallet = Some("default".to_string());
+    config.save().unwrap();
+
+    let mut config = Config::load_with(&Overrides {
+        config: Some(config.path.clone()),
+        rpc_url: Some("http://127.0.0.1:7000".to_string()),
+        wallet: Some("other".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(config.wallet.as_deref(), Some("other"));
+    config.lock_timeout = Some(5);
+    config.save().unwrap();
+
+    let loaded = Config::load(config.path.clone());
+    assert_eq!(loaded.lock_timeout, Some(5));
+    assert_eq!(loaded.wallet.as_deref(), Some("default"));
+    assert!(matches!(
+        loaded.backend,
+        Some(ConfigBackend::Spaced { ref url, .. }) if url == "http://127.0.0.1:7218"
+    ));
+
+    // switching wallets in the session is the user's choice and is kept
+    config.wallet = Some("third".to_string());
+    config.save().unwrap();
+    let loaded = Config::load(config.path.clone());
+    assert_eq!(loaded.wallet.as_deref(), Some("third"));
+}
+
+#[test]
+fn encrypts_credentials_with_passphrase() {
+    let dir = TempDir::new();
+    let mut config = mock::config(&dir, "http://127.0.0.1:7218");
+    if let Some(ConfigBackend::Spaced {
+        token, password, ..
+    }) = config.backend.as_mut()
+    {
+        *token = "secret-token".to_string();
+        // plain text that happens to look like an encrypted value
+        *password = "enc:00".to_string();
+    }
+    config.set_passphrase("hunter2").unwrap();
+
+    let contents = fs::read_to_string(&config.path).unwrap();
+    assert!(!contents.contains("secret-token"));
+
+    let mut loaded = Config::load(config.path.clone());
+    assert!(loaded.is_locked());
+    assert_eq!(
+        loaded.unlock("wrong").unwrap_err(),
+        "Wrong passphrase".to_string()
+    );
+    loaded.unlock("hunter2").unwrap();
+    assert!(!loaded.is_locked());
+    assert!(matches!(
+        loaded.backend,
+        Some(ConfigBackend::Spaced { ref token, ref password, .. })
+            if token == "secret-token" && password == "enc:00"
+    ));
+
+    let mut forgotten = Config::load(config.path.clone());
+    forgotten.forget_secrets().unwrap();
+    assert!(!forgotten.is_locked());
+    assert!(matches!(
+        forgotten.backend,
+        Some(ConfigBackend::Spaced { ref token, ref password, .. })
+            if token.is_empty() && password.is_empty()
+    ));
+}
+
+#[test]
+fn validates_recipients_for_network() {
+    let upper = mock::ADDRESS.to_uppercase();
+    assert!(is_recipient_input(&upper));
+    assert_eq!(recipient_from_str(&upper).as_deref(), Some(mock::ADDRESS));
+    assert!(is_recipient_on_network(
+        mock::ADDRESS,
+        ExtendedNetwork::Regtest
+    ));
+    assert!(!is_recipient_on_network(
+        mock::ADDRESS,
+        ExtendedNetwork::Mainnet
+    ));
+
+    assert_eq!(recipient_from_str("@example").as_deref(), Some("@example"));
+    assert!(recipient_from_str("example").is_none());
+    assert!(recipient_from_str("@").is_none());
+}
+
+#[tokio::test]
+async fn rejects_credentials_over_http() {
+    let fingerprint = "00".repeat(32);
+    let options = || ClientOptions {
+        user: "user",
+        password: "password",
+        fingerprint: &fingerprint,
+        ..Default::default()
+    };
+    assert!(Client::new("http://example.com:7225", options()).is_err());
+    assert!(Client::new("https://example.com:7225", options()).is_ok());
+    // a proxy alone is fine over http, onion services often have no certificate
+    let proxy = ClientOptions {
+        proxy: "127.0.0.1:9050",
+        ..Default::default()
+    };
+    assert!(Client::new("http://example.com:7225", proxy).is_ok());
+}
+
+#[tokio::test]
+async fn estimates_fees_through_bitcoind() {
+    let node = mock::MockSpaced::start().await;
+    let bitcoind = BitcoindClient::new(node.url(), None).unwrap();
+    assert_eq!(
+        mock::outputs(bitcoind.estimate_fees()).await,
+        vec![Ok(FeeEstimates {
+            fast: 13,
+            normal: 5,
+            slow: 1,
+        })]
+    );
+    assert_eq!(node.calls("estimatesmartfee").len(), 3);
+
+    // bitcoind answers without a fee rate until it has seen enough blocks
+    node.respond(
+        "estimatesmartfee",
+        Ok(json!({ "errors": ["Insufficient data or no feerate found"], "blocks": 0 })),
+    );
+    assert!(mock::outputs(bitcoind.estimate_fees()).await[0].is_err());
+}
+
+#[test]
+fn masks_amounts_when_hidden() {
+    let amount = Amount::from_sat(1_234_567);
+    assert_eq!(format_amount(amount, false), "1\u{2009}234\u{2009}567 sat");
+    let hidden = format_amount(amount, true);
+    assert!(!hidden.contains(char::is_numeric));
+    assert_eq!(hidden, format_amount(Amount::ZERO, true));
+}
+
+#[test]
+fn parses_payments_csv() {
+    let payments = payments_from_csv(&format!(
+        "Recipient,Amount\n{0},1000\n\n@example,2000\n",
+        mock::ADDRESS
+    ))
+    .unwrap();
+    assert_eq!(
+        payments,
+        vec![
+            (mock::ADDRESS.to_string(), Amount::from_sat(1000)),
+            ("@example".to_string(), Amount::from_sat(2000)),
+        ]
+    );
+
+    // only a known header is skipped, a bad first line is reported like any other
+    assert_eq!(
+        payments_from_csv(&format!("{},1k\n@example,2000", mock::ADDRESS)),
+        Err("Line 1: invalid amount".to_string())
+    );
+    assert_eq!(
+        payments_from_csv("@example,2000\n@example,lots"),
+        Err("Line 2: invalid amount".to_string())
+    );
+    assert_eq!(
+        payments_from_csv("address,amount\naddress,amount"),
+        Err("Line 2: invalid recipient".to_string())
+    );
+    assert!(payments_from_csv("recipient,amount\n").is_err());
+}
+
+#[test]
+fn imports_and_exports_contacts() {
+    let dir = TempDir::new();
+    let mut book = AddressBook::load(dir.path());
+    assert!(book.contacts().is_empty());
+
+    book.import(&format!(
+        "label,recipient\n\"Doe, Jane\",{}\nExample,@example\n",
+        mock::ADDRESS.to_uppercase()
+    ))
+    .unwrap();
+    assert!(book.import("Broken,nowhere").is_err());
+    // only a label,recipient header is skipped, a broken first line is reported
+    assert!(book.import("Broken,nowhere\nExample,@example\n").is_err());
+    book.save().unwrap();
+
+    let book = AddressBook::load(dir.path());
+    assert!(book.load_error().is_none());
+    assert_eq!(book.contacts().len(), 2);
+    assert_eq!(book.contacts()[0].label, "Doe, Jane");
+    assert_eq!(book.contacts()[0].recipient, mock::ADDRESS);
+    assert_eq!(book.contacts()[1].recipient, "@example");
+    // send only offers what can be paid on the wallet's network
+    assert_eq!(book.contacts_on_network(ExtendedNetwork::Regtest).len(), 2);
+    assert_eq!(
+        book.contacts_on_network(ExtendedNetwork::Mainnet),
+        vec![book.contacts()[1].clone()]
+    );
+
+    for format in [ContactsFormat::Json, ContactsFormat::Csv] {
+        let copy_dir = TempDir::new();
+        let mut copy = AddressBook::load(copy_dir.path());
+        copy.import(&book.export(format)).unwrap();
+        assert_eq!(copy.contacts(), book.contacts());
+    }
+}
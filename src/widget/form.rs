@@ -1,12 +1,14 @@
 use iced::{
     Background, Border, Center, Element, Fill, Font, Shrink, Theme,
     widget::{
-        Button, Column, Container, PickList, Text, TextInput, button, column,
+        Button, Column, Container, PickList, Row, Text, TextInput, button, checkbox, column,
         pick_list as _pick_list, text_editor, text_input as _text_input,
     },
 };
 use std::borrow::Borrow;
 
+use spaces_wallet::bitcoin::FeeRate;
+
 pub fn text_label(text: &str) -> Text<'_> {
     Text::new(text).size(14)
 }
@@ -111,6 +113,144 @@ impl<'a, Message: Clone + 'a> Form<'a, Message> {
         self
     }
 
+    /// Fee rate input with presets from the node's estimates, if there are any.
+    /// Fee rate input with the slow, normal and fast `presets` when they are known, an empty
+    /// field stands for the normal one.
+    pub fn add_fee_rate_input(
+        mut self,
+        value: &'a str,
+        presets: Option<[FeeRate; 3]>,
+        on_input: impl Fn(String) -> Message + 'a,
+    ) -> Self {
+        let mut element = column![text_label("Fee rate")].spacing(5);
+        let buttons = presets.map(|presets| {
+            let mut labels = ["Slow", "Normal", "Fast"].into_iter();
+            presets.map(|rate| {
+                let rate = rate.to_sat_per_vb_ceil();
+                Button::new(Text::new(format!("{} ({})", labels.next().unwrap(), rate)))
+                    .on_press(on_input(rate.to_string()))
+                    .padding([5, 10])
+                    .style(|theme: &Theme, status: button::Status| {
+                        let mut style = button::secondary(theme, status);
+                        style.border = style.border.rounded(7);
+                        style
+                    })
+                    .into()
+            })
+        });
+        element = element.push(
+            Row::new()
+                .push(
+                    text_input("sat/vB (auto if empty)", value)
+                        .on_input(on_input)
+                        .on_submit_maybe(self.submit_message.clone()),
+                )
+                .extend(buttons.into_iter().flatten())
+                .spacing(10)
+                .align_y(Center),
+        );
+        let hint = match (value.parse::<u64>(), presets) {
+            (Ok(rate), Some([_, _, fast])) if rate > fast.to_sat_per_vb_ceil().max(1) * 2 => {
+                Text::new(format!(
+                    "{} sat/vB is far above the current estimate of {} sat/vB",
+                    rate,
+                    fast.to_sat_per_vb_ceil()
+                ))
+                .style(|theme: &Theme| iced::widget::text::Style {
+                    color: Some(theme.extended_palette().danger.base.color),
+                })
+            }
+            (Ok(_), _) => Text::new(""),
+            (Err(_), Some([_, normal, _])) => Text::new(format!(
+                "Left empty, {} sat/vB from the current estimates",
+                normal.to_sat_per_vb_ceil()
+            )),
+            (Err(_), None) => Text::new(
+                "Left empty, the node picks the fee rate. Estimates need a bitcoind connection",
+            ),
+        };
+        element = element.push(hint.size(12));
+        self.elements.push(element.into());
+        self
+    }
+
+    pub fn add_secure_input(
+        mut self,
+        label: &'a str,
+        placeholder: &'a str,
+        value: &'a str,
+        on_input: impl Fn(String) -> Message + 'a,
+    ) -> Self {
+        self.elements.push(
+            column![
+                text_label(label),
+                text_input(placeholder, value)
+                    .secure(true)
+                    .on_input(on_input)
+                    .on_submit_maybe(self.submit_message.clone()),
+            ]
+            .spacing(5)
+            .into(),
+        );
+        self
+    }
+
+    pub fn add_checkbox(
+        mut self,
+        label: &'a str,
+        is_checked: bool,
+        on_toggle: impl Fn(bool) -> Message + 'a,
+    ) -> Self {
+        self.elements
+            .push(checkbox(label, is_checked).on_toggle(on_toggle).into());
+        self
+    }
+
+    pub fn add_buttons(
+        mut self,
+        buttons: impl IntoIterator<Item = (&'a str, Option<Message>)>,
+    ) -> Self {
+        self.elements.push(
+            Row::from_iter(buttons.into_iter().map(|(label, on_press)| {
+                Button::new(label)
+                    .on_press_maybe(on_press)
+                    .padding([5, 10])
+                    .style(|theme: &Theme, status: button::Status| {
+                        let mut style = button::secondary(theme, status);
+                        style.border = style.border.rounded(7);
+                        style
+                    })
+                    .into()
+            }))
+            .spacing(10)
+            .into(),
+        );
+        self
+    }
+
+    pub fn add_checklist<T: ToString + PartialEq + Clone + 'a>(
+        mut self,
+        label: &'a str,
+        options: &'a [T],
+        selected: &'a [T],
+        on_toggle: impl Fn(T, bool) -> Message + Clone + 'a,
+    ) -> Self {
+        self.elements.push(
+            column![text_label(label)]
+                .extend(options.iter().map(|option| {
+                    let on_toggle = on_toggle.clone();
+                    let value = option.clone();
+                    checkbox(option.to_string(), selected.contains(option))
+                        .font(Font::MONOSPACE)
+                        .on_toggle(move |checked| on_toggle(value.clone(), checked))
+                        .into()
+                }))
+                .spacing(5)
+                .into(),
+        );
+        self
+    }
+
     pub fn add_text_editor(
         mut self,
         label: &'a str,